@@ -0,0 +1,154 @@
+//! Derive macro for the `ProofInput` trait of `taceo-groth16-material`.
+//!
+//! Use it through the `derive` feature of `taceo-groth16-material`, which re-exports the macro as
+//! `taceo_groth16_material::circom::ProofInput`.
+#![deny(missing_docs)]
+
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, parse_macro_input, parse_quote};
+
+/// Derives `ProofInput` for a struct with named fields.
+///
+/// Every field becomes an input signal with the field name, or the name set with
+/// `#[proof_input(rename = "...")]`. The field types must implement `ProofInputValue`, which is
+/// implemented for `U256`, `ark_bn254::Fr`, integers, `bool` and (nested) fixed-size arrays of
+/// them. Arrays are flattened row-major, like Circom does for multi-dimensional signals.
+///
+/// # Example
+/// ```rust
+/// use ruint::aliases::U256;
+/// use taceo_groth16_material::circom::ProofInput;
+///
+/// #[derive(ProofInput)]
+/// struct MyInput {
+///     a: U256,
+///     #[proof_input(rename = "in")]
+///     matrix: [[u64; 2]; 3],
+///     flag: bool,
+/// }
+/// ```
+///
+/// # Errors
+/// The derive fails for tuple structs, unit structs and enums:
+/// ```rust,compile_fail
+/// # use taceo_groth16_material::circom::ProofInput;
+/// #[derive(ProofInput)]
+/// struct TupleInput(u64, u64);
+/// ```
+/// ```rust,compile_fail
+/// # use taceo_groth16_material::circom::ProofInput;
+/// #[derive(ProofInput)]
+/// enum EnumInput {
+///     A { a: u64 },
+/// }
+/// ```
+/// For attributes other than `rename`:
+/// ```rust,compile_fail
+/// # use taceo_groth16_material::circom::ProofInput;
+/// #[derive(ProofInput)]
+/// struct UnknownAttribute {
+///     #[proof_input(skip)]
+///     a: u64,
+/// }
+/// ```
+/// And for two fields with the same signal name:
+/// ```rust,compile_fail
+/// # use taceo_groth16_material::circom::ProofInput;
+/// #[derive(ProofInput)]
+/// struct DuplicateSignal {
+///     a: u64,
+///     #[proof_input(rename = "a")]
+///     b: u64,
+/// }
+/// ```
+#[proc_macro_derive(ProofInput, attributes(proof_input))]
+pub fn derive_proof_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "ProofInput can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "ProofInput can only be derived for structs",
+            ));
+        }
+    };
+
+    let krate = quote!(::taceo_groth16_material::circom);
+    let mut inserts = Vec::with_capacity(fields.len());
+    let mut field_types = Vec::with_capacity(fields.len());
+    let mut names = HashSet::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().expect("fields are named");
+        let mut name = ident.to_string();
+        if let Some(raw) = name.strip_prefix("r#") {
+            name = raw.to_owned();
+        }
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("proof_input"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported proof_input attribute, expected `rename`"))
+                }
+            })?;
+        }
+        if !names.insert(name.clone()) {
+            return Err(syn::Error::new_spanned(
+                field,
+                format!("duplicate input signal `{name}`"),
+            ));
+        }
+        inserts.push(quote! {
+            let mut values = ::std::vec::Vec::new();
+            #krate::ProofInputValue::append_values(&self.#ident, &mut values);
+            input.insert(::std::string::String::from(#name), values);
+        });
+        field_types.push(field.ty.clone());
+    }
+
+    // every field type must be an input value, which also covers generic fields
+    let where_clause = input.generics.make_where_clause();
+    for ty in field_types {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: #krate::ProofInputValue));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident = &input.ident;
+    let num_fields = inserts.len();
+    Ok(quote! {
+        impl #impl_generics #krate::ProofInput for #ident #ty_generics #where_clause {
+            fn prepare_input(
+                &self,
+            ) -> ::std::collections::HashMap<::std::string::String, ::std::vec::Vec<#krate::__private::U256>> {
+                let mut input = ::std::collections::HashMap::with_capacity(#num_fields);
+                #(#inserts)*
+                input
+            }
+        }
+    })
+}
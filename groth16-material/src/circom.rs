@@ -5,12 +5,15 @@ use ark_bn254::Bn254;
 use ark_ff::AdditiveGroup as _;
 use ark_ff::Field as _;
 use ark_ff::LegendreSymbol;
+use ark_ff::PrimeField as _;
 use ark_ff::UniformRand as _;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
+use circom_types::CheckElement;
 use circom_witness_rs::Graph;
 use groth16::CircomReduction;
 use groth16::Groth16;
+use groth16::ProverContext;
 use rand::{CryptoRng, Rng};
 use ruint::aliases::U256;
 use sha2::Digest as _;
@@ -21,6 +24,28 @@ use std::{collections::HashMap, path::Path};
 
 use crate::Groth16Error;
 
+#[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+mod cache;
+mod json_input;
+mod proof_bundle;
+mod public_signals;
+mod registry;
+#[cfg(test)]
+mod test_utils;
+mod validation;
+#[cfg(feature = "wasm-witness")]
+mod wasm_witness;
+
+pub use json_input::JsonProofInput;
+pub use proof_bundle::ProofBundle;
+pub use public_signals::{PublicSignal, PublicSignalNames, PublicSignals};
+pub use registry::{
+    ArtifactSource, CircuitManifest, MaterialManifest, MaterialRegistry, RegistryError,
+};
+pub use validation::{
+    InputValidationError, MissingSignal, OutOfRangeValue, WrongLengthSignal, signal_hash,
+};
+
 pub use ark_groth16::Proof;
 pub use ark_serialize::Compress;
 pub use ark_serialize::Validate;
@@ -65,6 +90,80 @@ impl ProofInput for HashMap<String, Vec<U256>> {
     }
 }
 
+/// Derives [`ProofInput`](trait@ProofInput) for a struct with named fields of [`ProofInputValue`] types.
+#[cfg(feature = "derive")]
+pub use groth16_material_derive::ProofInput;
+
+/// A value of an input signal, used by `#[derive(ProofInput)]`.
+///
+/// Implemented for `U256`, `ark_bn254::Fr`, integers, `bool` and (nested) fixed-size arrays of them. Arrays are
+/// flattened row-major, like Circom does for multi-dimensional signals. Negative integers are mapped to `p - |x|`,
+/// where `p` is the modulus of the BN254 scalar field.
+pub trait ProofInputValue {
+    /// Appends the field elements of the value to `values`.
+    fn append_values(&self, values: &mut Vec<U256>);
+}
+
+impl ProofInputValue for U256 {
+    fn append_values(&self, values: &mut Vec<U256>) {
+        values.push(*self);
+    }
+}
+
+impl ProofInputValue for ark_bn254::Fr {
+    fn append_values(&self, values: &mut Vec<U256>) {
+        values.push(U256::from_limbs(self.into_bigint().0));
+    }
+}
+
+impl ProofInputValue for bool {
+    fn append_values(&self, values: &mut Vec<U256>) {
+        values.push(U256::from(*self));
+    }
+}
+
+macro_rules! impl_proof_input_value_unsigned {
+    ($($ty:ty),*) => {$(
+        impl ProofInputValue for $ty {
+            fn append_values(&self, values: &mut Vec<U256>) {
+                values.push(U256::from(*self));
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_proof_input_value_signed {
+    ($($ty:ty),*) => {$(
+        impl ProofInputValue for $ty {
+            fn append_values(&self, values: &mut Vec<U256>) {
+                let abs = U256::from(self.unsigned_abs());
+                if *self < 0 {
+                    values.push(U256::from_limbs(ark_bn254::Fr::MODULUS.0) - abs);
+                } else {
+                    values.push(abs);
+                }
+            }
+        }
+    )*};
+}
+
+impl_proof_input_value_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_proof_input_value_signed!(i8, i16, i32, i64, i128, isize);
+
+impl<T: ProofInputValue, const N: usize> ProofInputValue for [T; N] {
+    fn append_values(&self, values: &mut Vec<U256>) {
+        for value in self {
+            value.append_values(values);
+        }
+    }
+}
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use ruint::aliases::U256;
+}
+
 /// Errors that can occur while loading or parsing a `.zkey` or graph file.
 #[derive(Debug, thiserror::Error)]
 pub enum ZkeyError {
@@ -80,6 +179,12 @@ pub enum ZkeyError {
     /// Could not parse the graph file.
     #[error(transparent)]
     GraphInvalid(#[from] eyre::Report),
+    /// Could not parse the `.sym` file.
+    #[error("invalid sym file - {0}")]
+    SymInvalid(String),
+    /// The public signal names do not match the circuit.
+    #[error("public signal names do not match the circuit - {0}")]
+    PublicSignalNamesMismatch(String),
     /// Any I/O error encountered while reading the `.zkey` or graph file
     #[error(transparent)]
     IoError(#[from] std::io::Error),
@@ -118,6 +223,12 @@ impl From<ark_serialize::SerializationError> for ZkeyError {
     }
 }
 
+impl From<groth16::Groth16Error> for ZkeyError {
+    fn from(value: groth16::Groth16Error) -> Self {
+        Self::ZkeyInvalid(eyre::eyre!(value))
+    }
+}
+
 /// Core material for generating groth-16 zero-knowledge proofs based on Circom. Currently we only support `bn254` material, because the underlying witness extension library only support `bn254`.
 ///
 /// Holds the proving keys, constraint matrices and graphs for the witness extension.
@@ -127,10 +238,92 @@ impl From<ark_serialize::SerializationError> for ZkeyError {
 #[derive(Clone)]
 pub struct CircomGroth16Material {
     zkey: ArkZkey<Bn254>,
-    /// The graph for witness extension
-    graph: Graph,
-    /// The black-box functions needed for witness extension
-    bbfs: HashMap<String, BlackBoxFunction>,
+    /// The precomputed evaluation domain for proof generation
+    ctx: ProverContext<Bn254, CircomReduction>,
+    /// The graph or wasm module for witness extension
+    witness_calculator: WitnessCalculator,
+    /// The names of the public signals, if set
+    public_signal_names: Option<PublicSignalNames>,
+    /// The known names of the input signals, to name missing signals
+    input_signal_names: Vec<String>,
+}
+
+/// The format of the `.zkey` bytes passed to a [`CircomGroth16MaterialBuilder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ZkeyFormat {
+    /// Detects the format by the `zkey` magic number of snarkjs `.zkey` files, falling back to [`ZkeyFormat::Ark`].
+    #[default]
+    Auto,
+    /// The canonical arkworks serialization of an [`ArkZkey`], e.g., produced by `convert-zkey-to-ark`.
+    Ark,
+    /// The original `.zkey` file produced by snarkjs.
+    Circom,
+}
+
+impl ZkeyFormat {
+    /// The magic number at the start of a snarkjs `.zkey` file.
+    const CIRCOM_MAGIC: &'static [u8; 4] = b"zkey";
+
+    fn detect(self, zkey_bytes: &[u8]) -> Self {
+        match self {
+            Self::Auto if zkey_bytes.starts_with(Self::CIRCOM_MAGIC) => Self::Circom,
+            Self::Auto => Self::Ark,
+            format => format,
+        }
+    }
+}
+
+/// The format of the witness calculator passed to a [`CircomGroth16MaterialBuilder`] as the graph bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WitnessCalculatorFormat {
+    /// Detects a `.wasm` module by its magic number, falling back to [`WitnessCalculatorFormat::Graph`].
+    #[default]
+    Auto,
+    /// A `circom-witness-rs` graph file.
+    Graph,
+    /// The `.wasm` witness calculator generated by the Circom compiler (`--wasm`), executed with the `wasmi` interpreter.
+    ///
+    /// The wasm module implements all functions of the circuit itself, so black-box functions are not used.
+    #[cfg(feature = "wasm-witness")]
+    Wasm,
+}
+
+impl WitnessCalculatorFormat {
+    /// The magic number at the start of a `.wasm` module.
+    #[cfg(feature = "wasm-witness")]
+    const WASM_MAGIC: &'static [u8; 4] = b"\0asm";
+
+    fn detect(self, graph_bytes: &[u8]) -> Self {
+        match self {
+            #[cfg(feature = "wasm-witness")]
+            Self::Auto if graph_bytes.starts_with(Self::WASM_MAGIC) => Self::Wasm,
+            Self::Auto => {
+                let _ = graph_bytes;
+                Self::Graph
+            }
+            format => format,
+        }
+    }
+}
+
+/// The files a [`CircomGroth16Material`] is built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Artifact {
+    Zkey,
+    Graph,
+}
+
+/// The witness calculator of a [`CircomGroth16Material`].
+#[derive(Clone)]
+enum WitnessCalculator {
+    Graph {
+        /// The graph for witness extension
+        graph: Graph,
+        /// The black-box functions needed for witness extension
+        bbfs: HashMap<String, BlackBoxFunction>,
+    },
+    #[cfg(feature = "wasm-witness")]
+    Wasm(wasm_witness::WasmWitnessCalculator),
 }
 
 /// Builder for `CircomGroth16Material`.
@@ -151,9 +344,15 @@ pub struct CircomGroth16Material {
 pub struct CircomGroth16MaterialBuilder {
     compress: Compress,
     validate: Validate,
+    zkey_format: ZkeyFormat,
+    witness_calculator_format: WitnessCalculatorFormat,
     fingerprint_zkey: Option<String>,
     fingerprint_graph: Option<String>,
     bbfs: HashMap<String, BlackBoxFunction>,
+    public_signal_names: Option<PublicSignalNames>,
+    input_signal_names: Vec<String>,
+    #[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+    cache_dir: Option<std::path::PathBuf>,
 }
 
 /// Builder-style serializer for exporting [`CircomGroth16Material`] into binary representations.
@@ -167,9 +366,15 @@ impl Default for CircomGroth16MaterialBuilder {
         Self {
             compress: Compress::No,
             validate: Validate::Yes,
+            zkey_format: ZkeyFormat::Auto,
+            witness_calculator_format: WitnessCalculatorFormat::Auto,
             fingerprint_zkey: None,
             fingerprint_graph: None,
             bbfs: HashMap::default(),
+            public_signal_names: None,
+            input_signal_names: Vec::new(),
+            #[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+            cache_dir: None,
         }
     }
 }
@@ -180,9 +385,14 @@ impl CircomGroth16MaterialBuilder {
     /// Defaults:
     /// - `compress`: `Compress::No`
     /// - `validate`: `Validate::Yes`
+    /// - `zkey_format`: `ZkeyFormat::Auto`
+    /// - `witness_calculator_format`: `WitnessCalculatorFormat::Auto`
     /// - `fingerprint_zkey`: No fingerprint verification of the Zkey.
     /// - `fingerprint_graph`: No fingerprint verification of the graph.
     /// - `bbfs`: No black-box functions.
+    /// - `public_signal_names`: No names for the public inputs.
+    /// - `input_signal_names`: No names for the input signals.
+    /// - `cache_dir`: No caching of downloaded files.
     pub fn new() -> Self {
         Self::default()
     }
@@ -199,18 +409,76 @@ impl CircomGroth16MaterialBuilder {
         self
     }
 
+    /// Sets the format of the `.zkey` file. See [`ZkeyFormat`] for details.
+    ///
+    /// Snarkjs `.zkey` files are parsed with [`circom_types::groth16::Zkey::from_reader`], so they can be used without converting
+    /// them to an [`ArkZkey`] first. The compression mode only applies to [`ZkeyFormat::Ark`].
+    pub fn zkey_format(mut self, zkey_format: ZkeyFormat) -> Self {
+        self.zkey_format = zkey_format;
+        self
+    }
+
+    /// Sets the format of the witness calculator passed as the graph file. See [`WitnessCalculatorFormat`] for details.
+    ///
+    /// With the `wasm-witness` feature, the `.wasm` witness calculator generated by Circom can be used for circuits that
+    /// `circom-witness-rs` cannot produce a graph for.
+    pub fn witness_calculator_format(
+        mut self,
+        witness_calculator_format: WitnessCalculatorFormat,
+    ) -> Self {
+        self.witness_calculator_format = witness_calculator_format;
+        self
+    }
+
     /// Sets the expected SHA-256 fingerprint for the `.zkey` file. If provided, the fingerprint will be verified during loading.
+    ///
+    /// The fingerprint is computed over the file as it is passed to the builder, i.e., over the original snarkjs file for
+    /// [`ZkeyFormat::Circom`].
     pub fn fingerprint_zkey(mut self, fingerprint_zkey: String) -> Self {
         self.fingerprint_zkey = Some(fingerprint_zkey);
         self
     }
 
-    /// Sets the expected SHA-256 fingerprint for the graph file. If provided, the fingerprint will be verified during loading.
+    /// Sets the expected SHA-256 fingerprint for the graph (or `.wasm`) file. If provided, the fingerprint will be verified during loading.
     pub fn fingerprint_graph(mut self, fingerprint_graph: String) -> Self {
         self.fingerprint_graph = Some(fingerprint_graph);
         self
     }
 
+    /// Sets a directory to cache the files downloaded by [`CircomGroth16MaterialBuilder::build_from_urls`].
+    ///
+    /// Downloaded files are stored under their SHA-256 fingerprint and reused as long as the fingerprint matches, so only files
+    /// with a fingerprint set with [`CircomGroth16MaterialBuilder::fingerprint_zkey`] or
+    /// [`CircomGroth16MaterialBuilder::fingerprint_graph`] are cached. Files are written atomically and interrupted downloads are
+    /// resumed on the next call.
+    #[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+    pub fn cache_dir(mut self, cache_dir: impl Into<std::path::PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Sets the names of the public signals, see [`PublicSignalNames`].
+    ///
+    /// The names are checked against the number of public inputs of the `.zkey` and the input signals of the graph when
+    /// building the material. They are required for [`CircomGroth16Material::public_signals`].
+    pub fn public_signal_names(mut self, public_signal_names: PublicSignalNames) -> Self {
+        self.public_signal_names = Some(public_signal_names);
+        self
+    }
+
+    /// Sets the names of the input signals of the circuit.
+    ///
+    /// The witness graph only contains the hashes of the input signal names, so
+    /// [`CircomGroth16Material::validate_inputs`] can only name missing signals whose names are set here or with
+    /// [`CircomGroth16MaterialBuilder::public_signal_names`]. Names that are not input signals of the circuit are ignored.
+    pub fn input_signal_names(
+        mut self,
+        input_signal_names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.input_signal_names = input_signal_names.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Adds custom black-box functions for witness extension. See [circom_witness_rs::BlackBoxFunction] for details.
     pub fn add_bbfs(mut self, bbfs: HashMap<String, BlackBoxFunction>) -> Self {
         self.bbfs.extend(bbfs);
@@ -370,46 +638,125 @@ impl CircomGroth16MaterialBuilder {
             self.validate
         };
 
-        let zkey = circom_types::groth16::ArkZkey::deserialize_with_mode(
-            zkey_bytes,
-            self.compress,
-            validate,
-        )?;
+        let zkey = match self.zkey_format.detect(zkey_bytes) {
+            ZkeyFormat::Circom => {
+                let check = match validate {
+                    Validate::Yes => CheckElement::Yes,
+                    Validate::No => CheckElement::No,
+                };
+                circom_types::groth16::Zkey::<Bn254>::from_reader(zkey_bytes, check)?.into()
+            }
+            _ => ArkZkey::deserialize_with_mode(zkey_bytes, self.compress, validate)?,
+        };
         if let Some(should_fingerprint) = self.fingerprint_graph {
             let is_fingerprint = hex::encode(sha2::Sha256::digest(graph_bytes));
             if is_fingerprint != should_fingerprint {
                 return Err(ZkeyError::GraphFingerprintMismatch(is_fingerprint));
             }
         }
-        let graph = circom_witness_rs::init_graph(graph_bytes).map_err(ZkeyError::GraphInvalid)?;
+        let witness_calculator = match self.witness_calculator_format.detect(graph_bytes) {
+            #[cfg(feature = "wasm-witness")]
+            WitnessCalculatorFormat::Wasm => WitnessCalculator::Wasm(
+                wasm_witness::WasmWitnessCalculator::new(graph_bytes)
+                    .map_err(ZkeyError::GraphInvalid)?,
+            ),
+            _ => WitnessCalculator::Graph {
+                graph: circom_witness_rs::init_graph(graph_bytes)
+                    .map_err(ZkeyError::GraphInvalid)?,
+                bbfs: self.bbfs,
+            },
+        };
+        let public_signal_names = self
+            .public_signal_names
+            .map(|names| {
+                let graph = match &witness_calculator {
+                    WitnessCalculator::Graph { graph, .. } => Some(graph),
+                    #[cfg(feature = "wasm-witness")]
+                    WitnessCalculator::Wasm(_) => None,
+                };
+                let num_public_inputs = zkey
+                    .pk
+                    .vk
+                    .gamma_abc_g1
+                    .len()
+                    .checked_sub(1)
+                    .ok_or(groth16::Groth16Error::MalformedVerifyingKey)?;
+                names.resolve(num_public_inputs, graph)
+            })
+            .transpose()?;
+        let mut input_signal_names = self.input_signal_names;
+        if let Some(names) = &public_signal_names {
+            input_signal_names.extend(names.signals().iter().map(|signal| signal.name.clone()));
+        }
+        let (matrices, pk) = zkey.as_inner();
+        let ctx = ProverContext::new(pk, matrices)?;
         Ok(CircomGroth16Material {
             zkey,
-            graph,
-            bbfs: self.bbfs,
+            ctx,
+            witness_calculator,
+            public_signal_names,
+            input_signal_names,
         })
     }
 
+    /// Returns the cache entry of the artifact, if it is cached.
+    #[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+    fn cached_artifact(&self, artifact: Artifact) -> Option<cache::CachedArtifact> {
+        let dir = self.cache_dir.as_ref()?;
+        match artifact {
+            Artifact::Zkey => self.fingerprint_zkey.as_deref().map(|fingerprint| {
+                cache::CachedArtifact::new(dir, fingerprint, ZkeyError::ZkeyFingerprintMismatch)
+            }),
+            Artifact::Graph => self.fingerprint_graph.as_deref().map(|fingerprint| {
+                cache::CachedArtifact::new(dir, fingerprint, ZkeyError::GraphFingerprintMismatch)
+            }),
+        }
+    }
+
+    /// Downloads the artifact with the blocking reqwest client, using the cache directory if set.
+    #[cfg(feature = "reqwest-blocking")]
+    fn download_blocking(
+        &self,
+        artifact: Artifact,
+        url: impl reqwest::IntoUrl,
+    ) -> Result<Vec<u8>, ZkeyError> {
+        match self.cached_artifact(artifact) {
+            Some(cached) => cached.fetch_blocking(url),
+            None => Ok(reqwest::blocking::get(url)?.bytes()?.to_vec()),
+        }
+    }
+
     /// Downloads `.zkey` and graph files from the provided URLs and builds the Groth16 material.
+    ///
+    /// Uses the files in the cache directory if set, see [`CircomGroth16MaterialBuilder::cache_dir`].
     #[cfg(feature = "reqwest")]
     pub async fn build_from_urls(
         self,
         zkey_url: impl reqwest::IntoUrl,
         graph_url: impl reqwest::IntoUrl,
     ) -> Result<CircomGroth16Material, ZkeyError> {
-        let zkey_bytes = reqwest::get(zkey_url).await?.bytes().await?;
-        let graph_bytes = reqwest::get(graph_url).await?.bytes().await?;
+        let zkey_bytes = match self.cached_artifact(Artifact::Zkey) {
+            Some(cached) => cached.fetch(zkey_url).await?,
+            None => reqwest::get(zkey_url).await?.bytes().await?.to_vec(),
+        };
+        let graph_bytes = match self.cached_artifact(Artifact::Graph) {
+            Some(cached) => cached.fetch(graph_url).await?,
+            None => reqwest::get(graph_url).await?.bytes().await?.to_vec(),
+        };
         self.build_from_bytes(&zkey_bytes, &graph_bytes)
     }
 
     /// Downloads `.zkey` and graph files from the provided URLs and builds the Groth16 material. Uses the blocking reqwest client.
+    ///
+    /// Uses the files in the cache directory if set, see [`CircomGroth16MaterialBuilder::cache_dir`].
     #[cfg(feature = "reqwest-blocking")]
     pub fn build_from_urls_blocking(
         self,
         zkey_url: impl reqwest::IntoUrl,
         graph_url: impl reqwest::IntoUrl,
     ) -> Result<CircomGroth16Material, ZkeyError> {
-        let zkey_bytes = reqwest::blocking::get(zkey_url)?.bytes()?;
-        let graph_bytes = reqwest::blocking::get(graph_url)?.bytes()?;
+        let zkey_bytes = self.download_blocking(Artifact::Zkey, zkey_url)?;
+        let graph_bytes = self.download_blocking(Artifact::Graph, graph_url)?;
         self.build_from_bytes(&zkey_bytes, &graph_bytes)
     }
 }
@@ -442,21 +789,49 @@ impl CircomGroth16Material {
         &self.zkey
     }
 
-    /// Computes a witness vector from a circuit graph and inputs.
+    /// Checks the inputs against the input signals of the circuit.
+    ///
+    /// With a circuit graph, reports all missing, unknown and wrong-length signals and all values that are not
+    /// smaller than the modulus of the BN254 scalar field. Missing signals are named if their names are known, see
+    /// [`CircomGroth16MaterialBuilder::input_signal_names`]. With a `.wasm` witness calculator, the signals are
+    /// checked by the calculator during witness generation and only the values are checked here.
+    pub fn validate_inputs(
+        &self,
+        inputs: &HashMap<String, Vec<U256>>,
+    ) -> Result<(), InputValidationError> {
+        match &self.witness_calculator {
+            WitnessCalculator::Graph { graph, .. } => {
+                validation::validate_graph_inputs(graph, inputs, &self.input_signal_names)
+            }
+            #[cfg(feature = "wasm-witness")]
+            WitnessCalculator::Wasm(_) => validation::validate_range(inputs),
+        }
+    }
+
+    /// Computes a witness vector from a circuit graph (or `.wasm` witness calculator) and inputs.
+    ///
+    /// The inputs are checked with [`CircomGroth16Material::validate_inputs`] first.
     pub fn generate_witness(
         &self,
         inputs: &impl ProofInput,
     ) -> Result<Vec<ark_bn254::Fr>, Groth16Error> {
-        let witness = circom_witness_rs::calculate_witness(
-            inputs.prepare_input(),
-            &self.graph,
-            Some(&self.bbfs),
-        )
-        .map_err(Groth16Error::WitnessGeneration)?
-        .into_iter()
-        .map(|v| ark_bn254::Fr::from(ark_ff::BigInt(v.into_limbs())))
-        .collect::<Vec<_>>();
-        Ok(witness)
+        let inputs = inputs.prepare_input();
+        self.validate_inputs(&inputs)
+            .map_err(Groth16Error::InvalidInputs)?;
+        match &self.witness_calculator {
+            WitnessCalculator::Graph { graph, bbfs } => {
+                let witness = circom_witness_rs::calculate_witness(inputs, graph, Some(bbfs))
+                    .map_err(Groth16Error::WitnessGeneration)?
+                    .into_iter()
+                    .map(|v| ark_bn254::Fr::from(ark_ff::BigInt(v.into_limbs())))
+                    .collect::<Vec<_>>();
+                Ok(witness)
+            }
+            #[cfg(feature = "wasm-witness")]
+            WitnessCalculator::Wasm(calculator) => calculator
+                .calculate_witness(inputs)
+                .map_err(Groth16Error::WitnessGeneration),
+        }
     }
 
     /// Generates a Groth16 proof from a witness and verifies it.
@@ -471,13 +846,39 @@ impl CircomGroth16Material {
         let s = ark_bn254::Fr::rand(rng);
 
         let (matrices, pk) = self.zkey.as_inner();
-        let proof = Groth16::prove::<CircomReduction>(pk, r, s, matrices, witness)
+        let proof = Groth16::prove_with_context(&self.ctx, pk, r, s, matrices, witness)
             .map_err(Groth16Error::ProofGeneration)?;
 
         let inputs = witness[1..matrices.num_instance_variables].to_vec();
         Ok((proof, inputs))
     }
 
+    /// Generates a Groth16 proof from a Circom `.wtns` witness file, e.g., computed by the native witness generator or another system.
+    ///
+    /// The witness must be over the BN254 scalar field, start with the constant one and have `num_instance_variables +
+    /// num_witness_variables` values of the proving key. Otherwise, an error is returned before proving.
+    pub fn generate_proof_from_wtns<R: Rng + CryptoRng>(
+        &self,
+        reader: impl std::io::Read,
+        rng: &mut R,
+    ) -> Result<(Proof<Bn254>, Vec<ark_bn254::Fr>), Groth16Error> {
+        let witness = circom_types::Witness::<ark_bn254::Fr>::from_reader(reader)
+            .map_err(Groth16Error::WitnessParsing)?
+            .values;
+        let (matrices, _) = self.zkey.as_inner();
+        let expected = matrices.num_instance_variables + matrices.num_witness_variables;
+        if witness.len() != expected {
+            return Err(Groth16Error::InvalidWitnessLength {
+                expected,
+                actual: witness.len(),
+            });
+        }
+        if witness[0] != ark_bn254::Fr::ONE {
+            return Err(Groth16Error::InvalidWitnessConstant);
+        }
+        self.generate_proof_from_witness(&witness, rng)
+    }
+
     /// Generates a Groth16 proof from structured inputs.
     ///
     /// This internally computes the witness using the provided inputs and then generates the proof.
@@ -490,14 +891,96 @@ impl CircomGroth16Material {
         self.generate_proof_from_witness(&witness, rng)
     }
 
+    /// Generates a Groth16 proof like [`CircomGroth16Material::generate_proof`] and returns it as a [`ProofBundle`], e.g., to
+    /// export it to snarkjs or the Solidity verifier.
+    pub fn generate_proof_bundle<R: Rng + CryptoRng>(
+        &self,
+        inputs: &impl ProofInput,
+        rng: &mut R,
+    ) -> Result<ProofBundle, Groth16Error> {
+        self.generate_proof(inputs, rng).map(ProofBundle::from)
+    }
+
+    /// Generates a Groth16 proof from a witness in the deterministic mode.
+    ///
+    /// Instead of sampling `r` and `s` from an RNG, they are derived from the secret `seed` and the witness with
+    /// [`Groth16::derive_randomness`]. The same seed and witness always yield the same proof. This is meant for reproducible
+    /// test vectors and audits; the zero-knowledge property relies on the secrecy of the seed. Use
+    /// [`CircomGroth16Material::generate_proof_from_witness`] otherwise.
+    pub fn generate_proof_from_witness_deterministic(
+        &self,
+        witness: &[ark_bn254::Fr],
+        seed: &[u8; 32],
+    ) -> Result<(Proof<Bn254>, Vec<ark_bn254::Fr>), Groth16Error> {
+        let (matrices, pk) = self.zkey.as_inner();
+        let proof = Groth16::prove_deterministic(&self.ctx, pk, seed, matrices, witness)
+            .map_err(Groth16Error::ProofGeneration)?;
+
+        let inputs = witness[1..matrices.num_instance_variables].to_vec();
+        Ok((proof, inputs))
+    }
+
+    /// Generates a Groth16 proof from structured inputs in the deterministic mode.
+    ///
+    /// See [`CircomGroth16Material::generate_proof_from_witness_deterministic`].
+    pub fn generate_proof_deterministic(
+        &self,
+        inputs: &impl ProofInput,
+        seed: &[u8; 32],
+    ) -> Result<(Proof<Bn254>, Vec<ark_bn254::Fr>), Groth16Error> {
+        let witness = self.generate_witness(inputs)?;
+        self.generate_proof_from_witness_deterministic(&witness, seed)
+    }
+
     /// Verifies a Groth16 proof and accompanying public inputs using the verification key.
     pub fn verify_proof(
         &self,
         proof: &Proof<Bn254>,
         public_inputs: &[ark_bn254::Fr],
     ) -> Result<(), Groth16Error> {
-        Groth16::verify(&self.zkey.pk.vk, proof, public_inputs)
-            .map_err(|_| Groth16Error::InvalidProof)
+        Groth16::verify(&self.zkey.pk.vk, proof, public_inputs).map_err(|err| match err {
+            groth16::Groth16Error::InvalidProof => Groth16Error::InvalidProof,
+            err => Groth16Error::MalformedPublicInputs(err),
+        })
+    }
+
+    /// Returns the names of the public signals, if set with [`CircomGroth16MaterialBuilder::public_signal_names`].
+    pub fn public_signal_names(&self) -> Option<&PublicSignalNames> {
+        self.public_signal_names.as_ref()
+    }
+
+    /// Decodes the public inputs returned by [`CircomGroth16Material::generate_proof`] into named signals.
+    ///
+    /// Requires the names of the public signals, see [`CircomGroth16MaterialBuilder::public_signal_names`].
+    pub fn public_signals(
+        &self,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Result<PublicSignals, Groth16Error> {
+        self.public_signal_names
+            .as_ref()
+            .ok_or(Groth16Error::MissingPublicSignalNames)?
+            .decode(public_inputs)
+    }
+
+    /// Re-randomizes a Groth16 proof, so that it is unlinkable to the original proof.
+    ///
+    /// The re-randomized proof verifies for the same public inputs. See [`Groth16::rerandomize`] for details.
+    pub fn rerandomize_proof<R: Rng + CryptoRng>(
+        &self,
+        proof: &Proof<Bn254>,
+        rng: &mut R,
+    ) -> Proof<Bn254> {
+        Groth16::rerandomize(&self.zkey.pk.vk, proof, rng)
+    }
+
+    /// Re-randomizes a Groth16 proof in the Circom JSON format. See [`CircomGroth16Material::rerandomize_proof`].
+    pub fn rerandomize_circom_proof<R: Rng + CryptoRng>(
+        &self,
+        proof: &circom_types::groth16::Proof<Bn254>,
+        rng: &mut R,
+    ) -> circom_types::groth16::Proof<Bn254> {
+        let proof = Proof::from(proof.clone());
+        self.rerandomize_proof(&proof, rng).into()
     }
 }
 
@@ -510,7 +993,8 @@ impl<'a> CircomGroth16MaterialSerializer<'a> {
 
     /// Serializes the material into `(zkey_bytes, graph_bytes)`.
     ///
-    /// The graph bytes are encoded with `postcard` as `(nodes, signals, input_mapping)`.
+    /// The graph bytes are encoded with `postcard` as `(nodes, signals, input_mapping)`. A `.wasm` witness calculator is written
+    /// as-is.
     ///
     /// # Example
     /// ```rust,no_run
@@ -539,15 +1023,17 @@ impl<'a> CircomGroth16MaterialSerializer<'a> {
             .zkey
             .serialize_with_mode(&mut zkey_writer, self.compress)
             .map_err(MaterialSerializationError::ZkeySerialization)?;
-        postcard::to_io(
-            &(
-                &self.material.graph.nodes,
-                &self.material.graph.signals,
-                &self.material.graph.input_mapping,
-            ),
-            &mut graph_writer,
-        )
-        .map_err(MaterialSerializationError::GraphSerialization)?;
+        match &self.material.witness_calculator {
+            WitnessCalculator::Graph { graph, .. } => {
+                postcard::to_io(
+                    &(&graph.nodes, &graph.signals, &graph.input_mapping),
+                    &mut graph_writer,
+                )
+                .map_err(MaterialSerializationError::GraphSerialization)?;
+            }
+            #[cfg(feature = "wasm-witness")]
+            WitnessCalculator::Wasm(calculator) => graph_writer.write_all(calculator.wasm())?,
+        }
         Ok(())
     }
 
@@ -567,3 +1053,213 @@ impl<'a> CircomGroth16MaterialSerializer<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+
+    use super::test_utils::*;
+    use super::*;
+
+    #[test]
+    fn rerandomize_proof() {
+        let material = material();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let (proof, public_inputs) = material.generate_proof(&inputs(3, 11), &mut rng).unwrap();
+        assert_eq!(public_inputs, [ark_bn254::Fr::from(33)]);
+
+        let rerandomized = material.rerandomize_proof(&proof, &mut rng);
+        assert_ne!(rerandomized, proof);
+        material
+            .verify_proof(&rerandomized, &public_inputs)
+            .unwrap();
+
+        let circom_proof = circom_types::groth16::Proof::<Bn254>::from(proof.clone());
+        let rerandomized = material.rerandomize_circom_proof(&circom_proof, &mut rng);
+        let json = serde_json::to_string(&rerandomized).unwrap();
+        let rerandomized = serde_json::from_str::<circom_types::groth16::Proof<Bn254>>(&json)
+            .unwrap()
+            .into();
+        assert_ne!(rerandomized, proof);
+        material
+            .verify_proof(&rerandomized, &public_inputs)
+            .unwrap();
+        assert!(matches!(
+            material.verify_proof(&rerandomized, &[ark_bn254::Fr::from(34)]),
+            Err(Groth16Error::InvalidProof)
+        ));
+    }
+
+    fn ark_bytes(material: &CircomGroth16Material) -> Vec<u8> {
+        material.serializer().to_bytes().unwrap().0
+    }
+
+    #[test]
+    fn loads_both_zkey_formats() {
+        let circom_bytes = zkey_bytes();
+        assert_eq!(ZkeyFormat::Auto.detect(&circom_bytes), ZkeyFormat::Circom);
+        let material = material();
+        let ark = ark_bytes(&material);
+        assert_eq!(ZkeyFormat::Auto.detect(&ark), ZkeyFormat::Ark);
+        assert_eq!(ZkeyFormat::Ark.detect(&circom_bytes), ZkeyFormat::Ark);
+
+        let build = |format, zkey: &[u8]| {
+            CircomGroth16MaterialBuilder::new()
+                .zkey_format(format)
+                .build_from_bytes(zkey, &graph_bytes())
+        };
+        for (format, zkey) in [
+            (ZkeyFormat::Auto, &circom_bytes),
+            (ZkeyFormat::Circom, &circom_bytes),
+            (ZkeyFormat::Auto, &ark),
+            (ZkeyFormat::Ark, &ark),
+        ] {
+            let material = build(format, zkey).unwrap();
+            assert_eq!(ark_bytes(&material), ark, "{format:?}");
+            let (proof, public_inputs) = material
+                .generate_proof_deterministic(&inputs(3, 11), &[0; 32])
+                .unwrap();
+            material.verify_proof(&proof, &public_inputs).unwrap();
+        }
+    }
+
+    #[test]
+    fn fingerprints_original_zkey_bytes() {
+        let circom_bytes = zkey_bytes();
+        let ark = ark_bytes(&material());
+        let fingerprint = |bytes: &[u8]| hex::encode(sha2::Sha256::digest(bytes));
+        let build = |zkey: &[u8], fingerprint_zkey: String| {
+            CircomGroth16MaterialBuilder::new()
+                .fingerprint_zkey(fingerprint_zkey)
+                .fingerprint_graph(fingerprint(&graph_bytes()))
+                .build_from_bytes(zkey, &graph_bytes())
+        };
+
+        build(&circom_bytes, fingerprint(&circom_bytes)).unwrap();
+        build(&ark, fingerprint(&ark)).unwrap();
+        // the fingerprint is not computed over the converted proving key
+        let err = build(&circom_bytes, fingerprint(&ark)).err().unwrap();
+        assert!(
+            matches!(&err, ZkeyError::ZkeyFingerprintMismatch(is) if *is == fingerprint(&circom_bytes)),
+            "{err}"
+        );
+        let err = CircomGroth16MaterialBuilder::new()
+            .fingerprint_graph(fingerprint(&ark))
+            .build_from_bytes(&circom_bytes, &graph_bytes())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, ZkeyError::GraphFingerprintMismatch(_)),
+            "{err}"
+        );
+    }
+
+    /// A `.wtns` file with the header of the KAT witness and the given values.
+    fn wtns(values: &[u64]) -> Vec<u8> {
+        // magic, version, number of sections and the header section up to the number of values
+        let mut wtns = kat("witness.wtns")[..60].to_vec();
+        wtns.extend((values.len() as u32).to_le_bytes());
+        wtns.extend(2u32.to_le_bytes());
+        wtns.extend((values.len() as u64 * 32).to_le_bytes());
+        for value in values {
+            wtns.extend(U256::from(*value).to_le_bytes::<32>());
+        }
+        wtns
+    }
+
+    #[test]
+    fn proves_from_wtns() {
+        let material = material();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let kat_wtns = kat("witness.wtns");
+        assert_eq!(wtns(&[1, 33, 3, 11]), kat_wtns);
+
+        let (proof, public_inputs) = material
+            .generate_proof_from_wtns(kat_wtns.as_slice(), &mut rng)
+            .unwrap();
+        assert_eq!(public_inputs, [ark_bn254::Fr::from(33)]);
+        material.verify_proof(&proof, &public_inputs).unwrap();
+
+        assert!(matches!(
+            material.generate_proof_from_wtns(&kat_wtns[..100], &mut rng),
+            Err(Groth16Error::WitnessParsing(_))
+        ));
+        assert!(matches!(
+            material.generate_proof_from_wtns(wtns(&[1, 33, 3]).as_slice(), &mut rng),
+            Err(Groth16Error::InvalidWitnessLength {
+                expected: 4,
+                actual: 3
+            })
+        ));
+        assert!(matches!(
+            material.generate_proof_from_wtns(wtns(&[2, 33, 3, 11]).as_slice(), &mut rng),
+            Err(Groth16Error::InvalidWitnessConstant)
+        ));
+    }
+
+    #[test]
+    fn deterministic_proofs() {
+        let material = material();
+        let seed = [7u8; 32];
+        let (proof, public_inputs) = material
+            .generate_proof_deterministic(&inputs(3, 11), &seed)
+            .unwrap();
+        let (same, _) = material
+            .generate_proof_deterministic(&inputs(3, 11), &seed)
+            .unwrap();
+        assert_eq!(same, proof);
+        let witness = material.generate_witness(&inputs(3, 11)).unwrap();
+        let (from_witness, _) = material
+            .generate_proof_from_witness_deterministic(&witness, &seed)
+            .unwrap();
+        assert_eq!(from_witness, proof);
+        material.verify_proof(&proof, &public_inputs).unwrap();
+
+        let (other, other_inputs) = material
+            .generate_proof_from_witness_deterministic(&witness, &[8u8; 32])
+            .unwrap();
+        assert_ne!(other, proof);
+        assert_eq!(other_inputs, public_inputs);
+        material.verify_proof(&other, &other_inputs).unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[derive(ProofInput)]
+    struct Input<T> {
+        a: U256,
+        b: ark_bn254::Fr,
+        #[proof_input(rename = "in")]
+        matrix: [[i64; 2]; 3],
+        flag: bool,
+        generic: T,
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_proof_input() {
+        let input = Input {
+            a: U256::from(42),
+            b: -ark_bn254::Fr::from(1),
+            matrix: [[1, 2], [3, 4], [5, -6]],
+            flag: true,
+            generic: 7u8,
+        }
+        .prepare_input();
+        let modulus = U256::from_limbs(ark_bn254::Fr::MODULUS.0);
+        let expected = HashMap::from([
+            ("a".to_owned(), vec![U256::from(42)]),
+            ("b".to_owned(), vec![modulus - U256::from(1)]),
+            (
+                "in".to_owned(),
+                [1, 2, 3, 4, 5]
+                    .into_iter()
+                    .map(U256::from)
+                    .chain([modulus - U256::from(6)])
+                    .collect(),
+            ),
+            ("flag".to_owned(), vec![U256::from(1)]),
+            ("generic".to_owned(), vec![U256::from(7)]),
+        ]);
+        assert_eq!(input, expected);
+    }
+}
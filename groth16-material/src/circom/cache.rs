@@ -0,0 +1,382 @@
+//! Disk cache for the artifacts downloaded by [`CircomGroth16MaterialBuilder::build_from_urls`](super::CircomGroth16MaterialBuilder).
+//!
+//! Artifacts are stored in the cache directory under their SHA-256 fingerprint. A download is written to
+//! `<fingerprint>.part` first and renamed once its fingerprint is verified, so the cache never contains partial or
+//! corrupted artifacts. An interrupted download is resumed with an HTTP range request. If the resumed download does not
+//! match the fingerprint (e.g., because the partial download was stale), it is downloaded once more from scratch.
+//!
+//! The async [`CachedArtifact::fetch`] runs the file system operations and the hashing on the blocking thread pool of
+//! tokio, so they do not block the executor.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use sha2::Digest as _;
+
+use super::ZkeyError;
+
+/// An artifact with an expected fingerprint in the cache directory.
+#[derive(Clone)]
+pub(super) struct CachedArtifact {
+    path: PathBuf,
+    part_path: PathBuf,
+    fingerprint: String,
+    mismatch: fn(String) -> ZkeyError,
+}
+
+impl CachedArtifact {
+    /// `mismatch` creates the error if the downloaded artifact does not match the fingerprint.
+    pub(super) fn new(dir: &Path, fingerprint: &str, mismatch: fn(String) -> ZkeyError) -> Self {
+        Self {
+            path: dir.join(fingerprint),
+            part_path: dir.join(format!("{fingerprint}.part")),
+            fingerprint: fingerprint.to_owned(),
+            mismatch,
+        }
+    }
+
+    /// Returns the cached artifact if it exists and matches the fingerprint.
+    ///
+    /// A cached artifact with a wrong fingerprint (e.g., modified on disk) is removed.
+    fn cached(&self) -> Result<Option<Vec<u8>>, ZkeyError> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if hex::encode(sha2::Sha256::digest(&bytes)) == self.fingerprint {
+            Ok(Some(bytes))
+        } else {
+            std::fs::remove_file(&self.path)?;
+            Ok(None)
+        }
+    }
+
+    /// Opens the partial download and returns it with the number of bytes already downloaded.
+    fn open_part(&self) -> Result<(File, u64), ZkeyError> {
+        if let Some(dir) = self.part_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.part_path)?;
+        let len = file.metadata()?.len();
+        Ok((file, len))
+    }
+
+    /// Returns whether the response continues the partial download of `len` bytes.
+    ///
+    /// Otherwise, the server sent the whole artifact and the partial download is discarded.
+    fn is_resumed(
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        len: u64,
+    ) -> bool {
+        status == reqwest::StatusCode::PARTIAL_CONTENT
+            && headers
+                .get(reqwest::header::CONTENT_RANGE)
+                .and_then(|range| range.to_str().ok())
+                .is_some_and(|range| range.starts_with(&format!("bytes {len}-")))
+    }
+
+    /// Verifies the finished download and moves it into the cache.
+    fn finish(&self, mut file: File) -> Result<Vec<u8>, ZkeyError> {
+        file.flush()?;
+        file.sync_all()?;
+        drop(file);
+        let bytes = std::fs::read(&self.part_path)?;
+        let is_fingerprint = hex::encode(sha2::Sha256::digest(&bytes));
+        if is_fingerprint != self.fingerprint {
+            std::fs::remove_file(&self.part_path)?;
+            return Err((self.mismatch)(is_fingerprint));
+        }
+        std::fs::rename(&self.part_path, &self.path)?;
+        Ok(bytes)
+    }
+
+    /// Returns the cached artifact or downloads it from `url` into the cache.
+    #[cfg(feature = "reqwest")]
+    pub(super) async fn fetch(&self, url: impl reqwest::IntoUrl) -> Result<Vec<u8>, ZkeyError> {
+        if let Some(bytes) = self.blocking(Self::cached).await? {
+            return Ok(bytes);
+        }
+        let client = reqwest::Client::new();
+        let url = client.get(url).build()?.url().clone();
+        let (file, len) = self.blocking(Self::open_part).await?;
+        match self.download(&client, url.clone(), file, len).await {
+            // the partial download was removed, start over
+            Err(err) if len > 0 && is_mismatch(&err) => {
+                let (file, len) = self.blocking(Self::open_part).await?;
+                self.download(&client, url, file, len).await
+            }
+            result => result,
+        }
+    }
+
+    /// Downloads `url` into the partial download of `len` bytes and moves it into the cache.
+    #[cfg(feature = "reqwest")]
+    async fn download(
+        &self,
+        client: &reqwest::Client,
+        url: reqwest::Url,
+        file: File,
+        len: u64,
+    ) -> Result<Vec<u8>, ZkeyError> {
+        use tokio::io::AsyncWriteExt as _;
+
+        let mut request = client.get(url);
+        if len > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={len}-"));
+        }
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && len > 0 {
+            // the previous download was complete, but not moved into the cache
+            return self.blocking(move |this| this.finish(file)).await;
+        }
+        let mut response = response.error_for_status()?;
+        let mut file = tokio::fs::File::from_std(file);
+        if !Self::is_resumed(response.status(), response.headers(), len) {
+            file.set_len(0).await?;
+        }
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        let file = file.into_std().await;
+        self.blocking(move |this| this.finish(file)).await
+    }
+
+    /// Runs the file system operation `f` on the blocking thread pool of tokio.
+    #[cfg(feature = "reqwest")]
+    async fn blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, ZkeyError> + Send + 'static,
+    ) -> Result<T, ZkeyError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || f(&this))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    /// Returns the cached artifact or downloads it from `url` into the cache. Uses the blocking reqwest client.
+    #[cfg(feature = "reqwest-blocking")]
+    pub(super) fn fetch_blocking(&self, url: impl reqwest::IntoUrl) -> Result<Vec<u8>, ZkeyError> {
+        if let Some(bytes) = self.cached()? {
+            return Ok(bytes);
+        }
+        let client = reqwest::blocking::Client::new();
+        let url = client.get(url).build()?.url().clone();
+        let (file, len) = self.open_part()?;
+        match self.download_blocking(&client, url.clone(), file, len) {
+            // the partial download was removed, start over
+            Err(err) if len > 0 && is_mismatch(&err) => {
+                let (file, len) = self.open_part()?;
+                self.download_blocking(&client, url, file, len)
+            }
+            result => result,
+        }
+    }
+
+    /// Downloads `url` into the partial download of `len` bytes and moves it into the cache. Uses the blocking reqwest
+    /// client.
+    #[cfg(feature = "reqwest-blocking")]
+    fn download_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
+        url: reqwest::Url,
+        mut file: File,
+        len: u64,
+    ) -> Result<Vec<u8>, ZkeyError> {
+        let mut request = client.get(url);
+        if len > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={len}-"));
+        }
+        let response = request.send()?;
+        if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && len > 0 {
+            // the previous download was complete, but not moved into the cache
+            return self.finish(file);
+        }
+        let mut response = response.error_for_status()?;
+        if !Self::is_resumed(response.status(), response.headers(), len) {
+            file.set_len(0)?;
+        }
+        response.copy_to(&mut file)?;
+        self.finish(file)
+    }
+}
+
+/// Returns whether the download did not match the fingerprint, see [`CachedArtifact::finish`].
+fn is_mismatch(err: &ZkeyError) -> bool {
+    matches!(
+        err,
+        ZkeyError::ZkeyFingerprintMismatch(_) | ZkeyError::GraphFingerprintMismatch(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead as _, BufReader, Write as _};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// A local HTTP stand-in serving `content` with support for range requests.
+    ///
+    /// Returns the URL and the `Range` headers of all requests.
+    fn serve(content: Vec<u8>) -> (String, Arc<Mutex<Vec<Option<u64>>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/artifact", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let requests_clone = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(start) = line.strip_prefix("range: bytes=") {
+                        range = Some(start.trim_end_matches('-').parse::<u64>().unwrap());
+                    }
+                }
+                requests_clone.lock().unwrap().push(range);
+                let (status, body) = match range {
+                    Some(start) => (
+                        format!(
+                            "206 Partial Content\r\ncontent-range: bytes {start}-{}/{}",
+                            content.len() - 1,
+                            content.len()
+                        ),
+                        &content[start as usize..],
+                    ),
+                    None => ("200 OK".to_owned(), &content[..]),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "groth16-material-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[cfg(feature = "reqwest-blocking")]
+    #[test]
+    fn resumes_download_and_reuses_cache() {
+        let content = (0..10_000u32)
+            .flat_map(u32::to_le_bytes)
+            .collect::<Vec<_>>();
+        let fingerprint = hex::encode(sha2::Sha256::digest(&content));
+        let (url, requests) = serve(content.clone());
+        let dir = temp_dir("resume");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{fingerprint}.part")), &content[..1234]).unwrap();
+
+        let artifact = CachedArtifact::new(&dir, &fingerprint, ZkeyError::ZkeyFingerprintMismatch);
+        assert_eq!(artifact.fetch_blocking(&url).unwrap(), content);
+        assert_eq!(*requests.lock().unwrap(), vec![Some(1234)]);
+        assert!(dir.join(&fingerprint).exists());
+        assert!(!dir.join(format!("{fingerprint}.part")).exists());
+
+        // served from the cache without a request
+        assert_eq!(artifact.fetch_blocking(&url).unwrap(), content);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "reqwest-blocking")]
+    #[test]
+    fn rejects_wrong_fingerprint() {
+        let (url, _) = serve(b"not the artifact".to_vec());
+        let dir = temp_dir("mismatch");
+        let fingerprint = hex::encode(sha2::Sha256::digest(b"artifact"));
+        let artifact = CachedArtifact::new(&dir, &fingerprint, ZkeyError::GraphFingerprintMismatch);
+        assert!(matches!(
+            artifact.fetch_blocking(&url),
+            Err(ZkeyError::GraphFingerprintMismatch(_))
+        ));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "reqwest-blocking")]
+    #[test]
+    fn restarts_stale_partial_download() {
+        let content = b"artifact".repeat(1000);
+        let fingerprint = hex::encode(sha2::Sha256::digest(&content));
+        let (url, requests) = serve(content.clone());
+        let dir = temp_dir("stale");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{fingerprint}.part")),
+            b"stale".repeat(100),
+        )
+        .unwrap();
+
+        let artifact = CachedArtifact::new(&dir, &fingerprint, ZkeyError::ZkeyFingerprintMismatch);
+        assert_eq!(artifact.fetch_blocking(&url).unwrap(), content);
+        assert_eq!(*requests.lock().unwrap(), vec![Some(500), None]);
+        assert!(dir.join(&fingerprint).exists());
+        assert!(!dir.join(format!("{fingerprint}.part")).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn fetches_asynchronously() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let content = b"artifact".repeat(1000);
+        let fingerprint = hex::encode(sha2::Sha256::digest(&content));
+        let (url, requests) = serve(content.clone());
+        let dir = temp_dir("async");
+        std::fs::create_dir_all(&dir).unwrap();
+        let part = dir.join(format!("{fingerprint}.part"));
+
+        // resumes a partial download
+        std::fs::write(&part, &content[..1234]).unwrap();
+        let artifact = CachedArtifact::new(&dir, &fingerprint, ZkeyError::ZkeyFingerprintMismatch);
+        assert_eq!(runtime.block_on(artifact.fetch(&url)).unwrap(), content);
+        assert_eq!(*requests.lock().unwrap(), vec![Some(1234)]);
+        assert!(!part.exists());
+
+        // served from the cache without a request
+        assert_eq!(runtime.block_on(artifact.fetch(&url)).unwrap(), content);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        // restarts a stale partial download
+        std::fs::remove_file(dir.join(&fingerprint)).unwrap();
+        std::fs::write(&part, b"stale".repeat(100)).unwrap();
+        assert_eq!(runtime.block_on(artifact.fetch(&url)).unwrap(), content);
+        assert_eq!(requests.lock().unwrap()[1..], [Some(500), None]);
+
+        // a wrong fingerprint without a partial download fails right away
+        let wrong = hex::encode(sha2::Sha256::digest(b"other"));
+        let artifact = CachedArtifact::new(&dir, &wrong, ZkeyError::GraphFingerprintMismatch);
+        assert!(matches!(
+            runtime.block_on(artifact.fetch(&url)),
+            Err(ZkeyError::GraphFingerprintMismatch(_))
+        ));
+        assert_eq!(requests.lock().unwrap().len(), 4);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+//! Proof inputs in the `input.json` format of Circom and snarkjs.
+
+use std::collections::HashMap;
+
+use ark_ff::PrimeField as _;
+use ruint::aliases::U256;
+use serde::de;
+use serde_json::Value;
+
+use super::{ProofInput, ProofInputValue as _};
+
+/// Proof inputs read from a Circom `input.json` file.
+///
+/// Every signal is a value or a nested array of values, flattened row-major like Circom does for
+/// multi-dimensional signals. A value is a JSON number, a decimal string or a `0x`-prefixed hex
+/// string, optionally with a leading `-`. Like Circom and snarkjs, values of any size are reduced
+/// modulo the BN254 scalar field `p` and a negative value `-x` becomes `p - (x mod p)`.
+///
+/// # Example
+/// ```rust
+/// # use taceo_groth16_material::circom::{JsonProofInput, ProofInput};
+/// let input = JsonProofInput::from_reader(
+///     r#"{ "a": "3", "b": [[11, "-1"], ["0x10", "-0x2"]] }"#.as_bytes(),
+/// )
+/// .expect("valid input.json");
+/// assert_eq!(input.prepare_input()["b"].len(), 4);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonProofInput {
+    signals: HashMap<String, Vec<U256>>,
+}
+
+impl JsonProofInput {
+    /// Reads the inputs from an `input.json` file.
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Returns the flattened values of the signal `name`.
+    pub fn get(&self, name: &str) -> Option<&[U256]> {
+        self.signals.get(name).map(Vec::as_slice)
+    }
+}
+
+impl ProofInput for JsonProofInput {
+    fn prepare_input(&self) -> HashMap<String, Vec<U256>> {
+        self.signals.clone()
+    }
+}
+
+impl From<JsonProofInput> for HashMap<String, Vec<U256>> {
+    fn from(input: JsonProofInput) -> Self {
+        input.signals
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for JsonProofInput {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let signals = HashMap::<String, Value>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, value)| {
+                let mut values = Vec::new();
+                flatten(&value, &mut values)
+                    .map_err(|err| de::Error::custom(format!("invalid signal {name}: {err}")))?;
+                Ok((name, values))
+            })
+            .collect::<Result<_, D::Error>>()?;
+        Ok(Self { signals })
+    }
+}
+
+/// Appends the values of a (nested) array row-major to `values`.
+fn flatten(value: &Value, values: &mut Vec<U256>) -> Result<(), String> {
+    match value {
+        Value::Array(array) => array.iter().try_for_each(|value| flatten(value, values)),
+        Value::Number(number) => {
+            if let Some(number) = number.as_u64() {
+                number.append_values(values);
+            } else if let Some(number) = number.as_i64() {
+                number.append_values(values);
+            } else {
+                return Err(format!(
+                    "{number} is not an integer, use a decimal string for large values"
+                ));
+            }
+            Ok(())
+        }
+        Value::String(string) => {
+            parse_str(string)?.append_values(values);
+            Ok(())
+        }
+        value => Err(format!("expected a number, string or array, got {value}")),
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hex string with an optional leading `-`, reduced modulo `p`.
+fn parse_str(string: &str) -> Result<ark_bn254::Fr, String> {
+    let (negative, magnitude) = match string.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, string),
+    };
+    let value = if let Some(hex) = magnitude
+        .strip_prefix("0x")
+        .or_else(|| magnitude.strip_prefix("0X"))
+    {
+        if hex.is_empty() {
+            return Err(format!("{string}: missing hex digits"));
+        }
+        // an odd number of digits has an implicit leading zero
+        let hex = if hex.len() % 2 == 1 {
+            format!("0{hex}")
+        } else {
+            hex.to_owned()
+        };
+        let bytes = hex::decode(hex).map_err(|err| format!("{string}: {err}"))?;
+        ark_bn254::Fr::from_be_bytes_mod_order(&bytes)
+    } else {
+        if magnitude.is_empty() || !magnitude.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(format!("{string}: invalid decimal number"));
+        }
+        let ten = ark_bn254::Fr::from(10u64);
+        magnitude
+            .bytes()
+            .fold(ark_bn254::Fr::from(0u64), |value, digit| {
+                value * ten + ark_bn254::Fr::from(digit - b'0')
+            })
+    };
+    Ok(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_circom_input_json() {
+        let input = JsonProofInput::from_reader(
+            r#"{
+                "a": 3,
+                "b": "-1",
+                "c": [["0x1f", "11"], [-2, 0]],
+                "d": []
+            }"#
+            .as_bytes(),
+        )
+        .unwrap();
+        let p_minus = |x: u64| U256::from_limbs(ark_bn254::Fr::MODULUS.0) - U256::from(x);
+        assert_eq!(input.get("a"), Some([U256::from(3)].as_slice()));
+        assert_eq!(input.get("b"), Some([p_minus(1)].as_slice()));
+        assert_eq!(
+            input.get("c"),
+            Some([U256::from(31), U256::from(11), p_minus(2), U256::ZERO].as_slice())
+        );
+        assert_eq!(input.get("d"), Some([].as_slice()));
+        assert_eq!(input.prepare_input().len(), 4);
+    }
+
+    #[test]
+    fn reduces_values_modulo_the_field() {
+        let modulus = U256::from_limbs(ark_bn254::Fr::MODULUS.0);
+        let p_minus = |x: u64| modulus - U256::from(x);
+        // 2^256 + 5 = (2^256 mod p) + 5
+        let two_256 = (U256::MAX % modulus + U256::from(1)) % modulus;
+        let input = JsonProofInput::from_reader(
+            format!(
+                r#"{{
+                    "p": ["{modulus}", "0x{modulus:x}", "-{modulus}"],
+                    "above_p": ["{}", "-{}", "0X{:x}"],
+                    "large": ["115792089237316195423570985008687907853269984665640564039457584007913129639941", "0x10000000000000000000000000000000000000000000000000000000000000005"],
+                    "hex": ["-0x1", "0xabc", "-0xABC"]
+                }}"#,
+                modulus + U256::from(7),
+                modulus + U256::from(7),
+                modulus + U256::from(7),
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(input.get("p"), Some([U256::ZERO; 3].as_slice()));
+        assert_eq!(
+            input.get("above_p"),
+            Some([U256::from(7), p_minus(7), U256::from(7)].as_slice())
+        );
+        let large = two_256 + U256::from(5);
+        assert_eq!(input.get("large"), Some([large, large].as_slice()));
+        assert_eq!(
+            input.get("hex"),
+            Some([p_minus(1), U256::from(0xabc), p_minus(0xabc)].as_slice())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for json in [
+            r#"{ "a": 1.5 }"#,
+            r#"{ "a": [true] }"#,
+            r#"{ "a": "12ab" }"#,
+            r#"{ "a": "" }"#,
+            r#"{ "a": "-" }"#,
+            r#"{ "a": "0x" }"#,
+            r#"{ "a": "-0xg1" }"#,
+            r#"{ "a": "--1" }"#,
+            r#"{ "a": "+1" }"#,
+        ] {
+            let err = JsonProofInput::from_reader(json.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains("invalid signal a"), "{err}");
+        }
+    }
+}
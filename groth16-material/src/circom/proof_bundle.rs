@@ -0,0 +1,135 @@
+//! A proof with its public inputs, exported to the formats of snarkjs and the Solidity verifier.
+
+use ark_bn254::Bn254;
+use ark_groth16::Proof;
+use circom_types::groth16::PublicInput;
+
+/// A Groth16 proof with its public inputs, see [`CircomGroth16Material::generate_proof_bundle`](super::CircomGroth16Material::generate_proof_bundle).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofBundle {
+    /// The proof.
+    pub proof: Proof<Bn254>,
+    /// The public inputs of the proof.
+    pub public_inputs: Vec<ark_bn254::Fr>,
+}
+
+impl ProofBundle {
+    /// Creates a bundle from a proof and its public inputs.
+    pub fn new(proof: Proof<Bn254>, public_inputs: Vec<ark_bn254::Fr>) -> Self {
+        Self {
+            proof,
+            public_inputs,
+        }
+    }
+
+    /// Returns the proof and the public inputs in the format of the snarkjs `proof.json` and `public.json` files.
+    pub fn to_snarkjs_json(&self) -> (String, String) {
+        let proof = circom_types::groth16::Proof::<Bn254>::from(self.proof.clone());
+        let public_input = PublicInput(self.public_inputs.clone());
+        (
+            serde_json::to_string_pretty(&proof).expect("can serialize proof to JSON"),
+            serde_json::to_string_pretty(&public_input)
+                .expect("can serialize public inputs to JSON"),
+        )
+    }
+
+    /// Returns the arguments of `verifyProof` (or `verifyCompressedProof` if `compressed`) of the Solidity verifier generated
+    /// by `taceo-groth16-sol`, in the format of `groth16-sol-utils generate-call`: `[proof],[public inputs]`.
+    #[cfg(feature = "solidity")]
+    pub fn to_solidity_calldata(&self, compressed: bool) -> String {
+        use ark_ff::PrimeField as _;
+
+        let proof = if compressed {
+            join(groth16_sol::prepare_compressed_proof(&self.proof))
+        } else {
+            join(groth16_sol::prepare_uncompressed_proof(&self.proof))
+        };
+        let public_inputs = join(
+            self.public_inputs
+                .iter()
+                .map(|x| ruint::aliases::U256::from_limbs(x.into_bigint().0)),
+        );
+        format!("[{proof}],[{public_inputs}]")
+    }
+}
+
+#[cfg(feature = "solidity")]
+fn join(values: impl IntoIterator<Item = impl ToString>) -> String {
+    values
+        .into_iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl From<(Proof<Bn254>, Vec<ark_bn254::Fr>)> for ProofBundle {
+    fn from((proof, public_inputs): (Proof<Bn254>, Vec<ark_bn254::Fr>)) -> Self {
+        Self::new(proof, public_inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The proof of the `multiplier2` test vector of `taceo-groth16`.
+    const PROOF: &str = r#"{
+        "pi_a": [
+            "5969123522090814361171588228229368332719697989145919311329989202301051796912",
+            "18906266273883421538550545870389760028232642993789046435548759958047513826466",
+            "1"
+        ],
+        "pi_b": [
+            [
+                "13732822754685216699494313130307949314358351264391615026657641877459312805921",
+                "15242155868134051061519617910834758681213622395767565233201715494163382082631"
+            ],
+            [
+                "6040988303910179137905227500476692522731546381459192177262195830159275686930",
+                "6102931310051425482112222546940021723264293724138375749141717027794878004116"
+            ],
+            ["1", "0"]
+        ],
+        "pi_c": [
+            "8027438340805100823503975850514290391260085605647857333256305214246713987397",
+            "17368354082387796246978493062684369586080079518888794624836970993708830684295",
+            "1"
+        ],
+        "protocol": "groth16",
+        "curve": "bn128"
+    }"#;
+
+    fn bundle() -> ProofBundle {
+        let proof = serde_json::from_str::<circom_types::groth16::Proof<Bn254>>(PROOF).unwrap();
+        ProofBundle::new(proof.into(), vec![ark_bn254::Fr::from(33)])
+    }
+
+    #[test]
+    fn exports_snarkjs_json() {
+        let bundle = bundle();
+        let (proof, public_input) = bundle.to_snarkjs_json();
+        let proof = serde_json::from_str::<circom_types::groth16::Proof<Bn254>>(&proof).unwrap();
+        assert_eq!(proof, serde_json::from_str(PROOF).unwrap());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&public_input).unwrap(),
+            serde_json::json!(["33"])
+        );
+    }
+
+    #[cfg(feature = "solidity")]
+    #[test]
+    fn exports_solidity_calldata() {
+        let mut bundle = bundle();
+        bundle.public_inputs.push(ark_bn254::Fr::from(0));
+        let calldata = bundle.to_solidity_calldata(false);
+        assert!(calldata.starts_with(
+            "[5969123522090814361171588228229368332719697989145919311329989202301051796912,"
+        ));
+        assert!(calldata.ends_with("],[33,0]"));
+        assert_eq!(calldata.matches(',').count(), 7 + 1 + 1);
+        assert_eq!(
+            bundle.to_solidity_calldata(true).matches(',').count(),
+            3 + 1 + 1
+        );
+    }
+}
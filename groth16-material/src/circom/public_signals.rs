@@ -0,0 +1,298 @@
+//! Names of the public signals of a circuit.
+//!
+//! Circom orders the public inputs of a proof by the outputs of the main component first, followed by its public inputs.
+//! [`PublicSignalNames`] records this order, so that the public inputs can be decoded into named [`PublicSignals`].
+
+use std::collections::HashSet;
+use std::io::BufRead;
+
+use circom_types::groth16::PublicInput;
+use circom_witness_rs::Graph;
+
+use super::{ZkeyError, signal_hash};
+use crate::Groth16Error;
+
+/// A public signal of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct PublicSignal {
+    /// The name of the signal in the main component, without the `main.` prefix.
+    pub name: String,
+    /// The number of values of the signal, e.g., `6` for `signal output out[2][3]`.
+    pub size: usize,
+}
+
+impl PublicSignal {
+    /// Creates a public signal with `size` values.
+    pub fn new(name: impl Into<String>, size: usize) -> Self {
+        Self {
+            name: name.into(),
+            size,
+        }
+    }
+}
+
+/// The names of the public signals of a circuit, in the order of the public inputs of its proofs.
+///
+/// Set with [`CircomGroth16MaterialBuilder::public_signal_names`](super::CircomGroth16MaterialBuilder::public_signal_names)
+/// and checked against the circuit when building the material. In a [`CircuitManifest`](super::CircuitManifest), the names
+/// are a list of `{ "name": "out", "size": 1 }` objects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(transparent)]
+pub struct PublicSignalNames {
+    signals: Vec<PublicSignal>,
+    /// Read from a `.sym` file, which also contains the private signals of the main component.
+    #[serde(skip)]
+    from_sym: bool,
+}
+
+impl PublicSignalNames {
+    /// Creates the names from the public signals in Circom's order: the outputs of the main component, then its public
+    /// inputs.
+    pub fn new(signals: impl IntoIterator<Item = PublicSignal>) -> Self {
+        Self {
+            signals: signals.into_iter().collect(),
+            from_sym: false,
+        }
+    }
+
+    /// Reads the names from a `.sym` file generated by the Circom compiler with `--sym`.
+    ///
+    /// Every line of the file is `label,witness index,component,name`. The signals of the main component are read in the
+    /// order of the witness and the elements of arrays (e.g., `main.out[0]`, `main.out[1]`) are combined into one signal.
+    /// The private signals of the main component are removed when building the material, as the number of public inputs
+    /// is only known from the `.zkey`.
+    pub fn from_sym(reader: impl BufRead) -> Result<Self, ZkeyError> {
+        let mut entries = Vec::new();
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let invalid =
+                || ZkeyError::SymInvalid(format!("invalid line {}: {line}", line_idx + 1));
+            let mut columns = line.splitn(4, ',');
+            let (Some(_), Some(witness_idx), Some(_), Some(name)) = (
+                columns.next(),
+                columns.next(),
+                columns.next(),
+                columns.next(),
+            ) else {
+                return Err(invalid());
+            };
+            let witness_idx = witness_idx.parse::<i64>().map_err(|_| invalid())?;
+            // signals of sub-components and signals removed by the optimizer are skipped
+            if let Some(name) = name.strip_prefix("main.")
+                && !name.contains('.')
+                && witness_idx > 0
+            {
+                entries.push((witness_idx as u64, name.to_owned()));
+            }
+        }
+        entries.sort_unstable_by_key(|(witness_idx, _)| *witness_idx);
+
+        let mut signals = Vec::<PublicSignal>::new();
+        // the public signals directly follow the constant one of the witness
+        for (expected_idx, (witness_idx, name)) in (1..).zip(entries) {
+            if witness_idx != expected_idx {
+                break;
+            }
+            let base = name.split('[').next().unwrap_or(&name);
+            match signals.last_mut() {
+                Some(signal) if signal.name == base && name.contains('[') => signal.size += 1,
+                _ => signals.push(PublicSignal::new(base, 1)),
+            }
+        }
+        Ok(Self {
+            signals,
+            from_sym: true,
+        })
+    }
+
+    /// Returns the public signals.
+    pub fn signals(&self) -> &[PublicSignal] {
+        &self.signals
+    }
+
+    /// Returns the number of public inputs covered by the signals.
+    pub fn num_public_inputs(&self) -> usize {
+        self.signals.iter().map(|signal| signal.size).sum()
+    }
+
+    /// Checks the names against the number of public inputs of the circuit and the inputs of the graph, if any.
+    pub(super) fn resolve(
+        mut self,
+        num_public_inputs: usize,
+        graph: Option<&Graph>,
+    ) -> Result<Self, ZkeyError> {
+        let mismatch = |message: String| Err(ZkeyError::PublicSignalNamesMismatch(message));
+        if self.from_sym {
+            let mut total = 0;
+            let num_signals = self
+                .signals
+                .iter()
+                .take_while(|signal| {
+                    let is_public = total < num_public_inputs;
+                    total += signal.size;
+                    is_public
+                })
+                .count();
+            self.signals.truncate(num_signals);
+        }
+        let total = self.num_public_inputs();
+        if total != num_public_inputs {
+            return mismatch(format!(
+                "the names cover {total} values, but the circuit has {num_public_inputs} public inputs"
+            ));
+        }
+        let mut names = HashSet::new();
+        for signal in &self.signals {
+            if !names.insert(signal.name.as_str()) {
+                return mismatch(format!("duplicate signal {}", signal.name));
+            }
+            let hash = signal_hash(&signal.name);
+            if let Some(info) =
+                graph.and_then(|graph| graph.input_mapping.iter().find(|info| info.hash == hash))
+                && info.signalsize != signal.size as u64
+            {
+                return mismatch(format!(
+                    "signal {} has {} values in the graph, not {}",
+                    signal.name, info.signalsize, signal.size
+                ));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Decodes the public inputs of a proof into named signals.
+    pub fn decode(&self, public_inputs: &[ark_bn254::Fr]) -> Result<PublicSignals, Groth16Error> {
+        let expected = self.num_public_inputs();
+        if public_inputs.len() != expected {
+            return Err(Groth16Error::InvalidPublicInputsLength {
+                expected,
+                actual: public_inputs.len(),
+            });
+        }
+        let mut offset = 0;
+        let signals = self
+            .signals
+            .iter()
+            .map(|signal| {
+                let values = public_inputs[offset..offset + signal.size].to_vec();
+                offset += signal.size;
+                (signal.name.clone(), values)
+            })
+            .collect();
+        Ok(PublicSignals { signals })
+    }
+}
+
+/// The public inputs of a proof by the names of their signals, see [`PublicSignalNames`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicSignals {
+    signals: Vec<(String, Vec<ark_bn254::Fr>)>,
+}
+
+impl PublicSignals {
+    /// Returns the values of the signal `name`.
+    pub fn get(&self, name: &str) -> Option<&[ark_bn254::Fr]> {
+        self.signals
+            .iter()
+            .find(|(signal, _)| signal == name)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Returns the signals with their values in the order of the public inputs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[ark_bn254::Fr])> {
+        self.signals
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.as_slice()))
+    }
+
+    /// Returns the public inputs, as expected by [`CircomGroth16Material::verify_proof`](super::CircomGroth16Material::verify_proof).
+    pub fn public_inputs(&self) -> Vec<ark_bn254::Fr> {
+        self.signals
+            .iter()
+            .flat_map(|(_, values)| values.iter().copied())
+            .collect()
+    }
+
+    /// Returns the public inputs as a [`PublicInput`], which serializes to a snarkjs `public.json`.
+    pub fn to_public_input(&self) -> PublicInput<ark_bn254::Fr> {
+        PublicInput(self.public_inputs())
+    }
+
+    /// Returns the public inputs in the format of a snarkjs `public.json`.
+    pub fn to_public_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_public_input())
+            .expect("can serialize public inputs to JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYM: &str = "1,1,0,main.out[0]
+2,2,0,main.out[1]
+3,3,0,main.c
+4,4,0,main.a
+5,5,0,main.b
+6,-1,0,main.tmp
+7,6,1,main.sub.x
+";
+
+    #[test]
+    fn reads_sym_and_decodes_public_inputs() {
+        let names = PublicSignalNames::from_sym(SYM.as_bytes())
+            .unwrap()
+            .resolve(3, None)
+            .unwrap();
+        assert_eq!(
+            names.signals(),
+            [PublicSignal::new("out", 2), PublicSignal::new("c", 1)]
+        );
+
+        let public_inputs = [1, 2, 3].map(ark_bn254::Fr::from);
+        let signals = names.decode(&public_inputs).unwrap();
+        assert_eq!(signals.get("out"), Some(&public_inputs[..2]));
+        assert_eq!(signals.get("c"), Some(&public_inputs[2..]));
+        assert_eq!(signals.get("a"), None);
+        assert_eq!(signals.public_inputs(), public_inputs);
+        assert_eq!(
+            serde_json::from_str::<PublicInput<ark_bn254::Fr>>(&signals.to_public_json()).unwrap(),
+            PublicInput(public_inputs.to_vec())
+        );
+        assert!(matches!(
+            names.decode(&public_inputs[..2]),
+            Err(Groth16Error::InvalidPublicInputsLength {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatching_names() {
+        let from_sym = PublicSignalNames::from_sym(SYM.as_bytes()).unwrap();
+        // the public inputs would split `out`
+        assert!(matches!(
+            from_sym.clone().resolve(1, None),
+            Err(ZkeyError::PublicSignalNamesMismatch(_))
+        ));
+        assert!(from_sym.resolve(5, None).is_ok());
+
+        let names = PublicSignalNames::new([PublicSignal::new("out", 1)]);
+        assert!(names.clone().resolve(1, None).is_ok());
+        assert!(matches!(
+            names.resolve(2, None),
+            Err(ZkeyError::PublicSignalNamesMismatch(_))
+        ));
+        let duplicate =
+            PublicSignalNames::new([PublicSignal::new("a", 1), PublicSignal::new("a", 1)]);
+        assert!(matches!(
+            duplicate.resolve(2, None),
+            Err(ZkeyError::PublicSignalNamesMismatch(_))
+        ));
+        assert!(PublicSignalNames::from_sym("1,x,0,main.a".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,615 @@
+//! A registry of [`CircomGroth16Material`] for services proving many circuits.
+//!
+//! The circuits are described by a [`MaterialManifest`] and loaded lazily on first use. If a memory budget is set, the least
+//! recently used circuits are evicted when loading another circuit would exceed it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use ark_bn254::Bn254;
+use rand::{CryptoRng, Rng};
+
+use super::{
+    Artifact, BlackBoxFunction, CircomGroth16Material, CircomGroth16MaterialBuilder, ProofInput,
+    PublicSignalNames, ZkeyError,
+};
+use crate::Groth16Error;
+use ark_groth16::Proof;
+
+/// The location of a `.zkey` or graph file in a [`CircuitManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactSource {
+    /// A path on the local file system.
+    Path(PathBuf),
+    /// A URL, downloaded with the blocking reqwest client. Requires the `reqwest-blocking` feature.
+    Url(String),
+}
+
+/// The description of a circuit in a [`MaterialManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct CircuitManifest {
+    /// The name of the circuit in the registry.
+    pub name: String,
+    /// The `.zkey` file of the circuit.
+    pub zkey: ArtifactSource,
+    /// The graph (or `.wasm`) file for witness extension.
+    pub graph: ArtifactSource,
+    /// The expected SHA-256 fingerprint of the `.zkey` file.
+    #[serde(default)]
+    pub fingerprint_zkey: Option<String>,
+    /// The expected SHA-256 fingerprint of the graph file.
+    #[serde(default)]
+    pub fingerprint_graph: Option<String>,
+    /// The names of the black-box functions of the circuit.
+    ///
+    /// Either one of the standard functions of [`CircomGroth16MaterialBuilder`] (e.g., `bbf_inv`) or a function added with
+    /// [`MaterialRegistry::add_bbfs`].
+    #[serde(default)]
+    pub bbfs: Vec<String>,
+    /// The names of the public signals of the circuit, see [`PublicSignalNames`].
+    #[serde(default)]
+    pub public_signals: Option<PublicSignalNames>,
+    /// The names of the input signals of the circuit, see [`CircomGroth16MaterialBuilder::input_signal_names`].
+    #[serde(default)]
+    pub input_signals: Vec<String>,
+}
+
+/// The circuits of a [`MaterialRegistry`].
+///
+/// # Example
+/// ```json
+/// {
+///     "circuits": [
+///         {
+///             "name": "query",
+///             "zkey": { "path": "./query.zkey" },
+///             "graph": { "url": "https://example.com/query.graph" },
+///             "fingerprint_graph": "d1bc8d3ba4afc7e109612cb73acbdddac052c93025aa1f82942edabb7deb82a1",
+///             "bbfs": ["bbf_inv"],
+///             "public_signals": [{ "name": "nullifier", "size": 1 }, { "name": "root", "size": 1 }],
+///             "input_signals": ["secret", "root"]
+///         }
+///     ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct MaterialManifest {
+    /// The circuits.
+    pub circuits: Vec<CircuitManifest>,
+}
+
+impl MaterialManifest {
+    /// Reads a manifest in the JSON format.
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+}
+
+/// Errors of a [`MaterialRegistry`].
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    /// The manifest contains two circuits with the same name.
+    #[error("duplicate circuit {0} in manifest")]
+    DuplicateCircuit(String),
+    /// The circuit is not in the manifest.
+    #[error("unknown circuit {0}")]
+    UnknownCircuit(String),
+    /// A black-box function of the circuit is neither a standard function nor added to the registry.
+    #[error("unknown black-box function {bbf} for circuit {circuit}")]
+    UnknownBlackBoxFunction {
+        /// The name of the circuit.
+        circuit: String,
+        /// The name of the black-box function.
+        bbf: String,
+    },
+    /// The circuit is loaded from a URL, but the `reqwest-blocking` feature is not enabled.
+    #[error("circuit {0} is loaded from a URL, which requires the reqwest-blocking feature")]
+    UrlNotSupported(String),
+    /// The material of the circuit could not be loaded.
+    #[error("could not load circuit {circuit}")]
+    Load {
+        /// The name of the circuit.
+        circuit: String,
+        /// The error while loading.
+        #[source]
+        source: ZkeyError,
+    },
+    /// Witness generation, proof generation or verification failed.
+    #[error(transparent)]
+    Groth16(#[from] Groth16Error),
+}
+
+struct LoadedCircuit {
+    material: Arc<CircomGroth16Material>,
+    /// The estimated memory usage of the material in bytes.
+    size: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct LoadedCircuits {
+    circuits: HashMap<String, LoadedCircuit>,
+    size: usize,
+    clock: u64,
+}
+
+impl LoadedCircuits {
+    fn get(&mut self, name: &str) -> Option<Arc<CircomGroth16Material>> {
+        self.clock += 1;
+        let circuit = self.circuits.get_mut(name)?;
+        circuit.last_used = self.clock;
+        Some(Arc::clone(&circuit.material))
+    }
+
+    fn remove(&mut self, name: &str) -> bool {
+        match self.circuits.remove(name) {
+            Some(circuit) => {
+                self.size -= circuit.size;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A registry of [`CircomGroth16Material`] for many circuits, loaded lazily from a [`MaterialManifest`].
+///
+/// The memory usage of a circuit is estimated by the size of its `.zkey` and graph files. If a memory budget is set, the least
+/// recently used circuits are evicted before loading a circuit that would exceed the budget. A circuit that exceeds the budget on
+/// its own is still loaded. Proofs that are in progress keep their material alive until they finish.
+///
+/// Every circuit is loaded by at most one thread at a time: concurrent first requests for the same circuit wait for the load and
+/// share the loaded material, while other circuits can be used in the meantime.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use taceo_groth16_material::circom::{MaterialManifest, MaterialRegistry};
+/// # use std::collections::HashMap;
+/// let manifest = std::fs::File::open("./circuits.json").expect("manifest exists");
+/// let manifest = MaterialManifest::from_reader(manifest).expect("valid manifest");
+/// let registry = MaterialRegistry::new(manifest)
+///     .expect("unique circuit names")
+///     .memory_budget(4 << 30);
+/// let inputs = HashMap::new();
+/// let (proof, public_inputs) = registry
+///     .prove("query", &inputs, &mut rand::thread_rng())
+///     .expect("can prove");
+/// registry.verify("query", &proof, &public_inputs).expect("valid proof");
+/// ```
+pub struct MaterialRegistry {
+    manifests: HashMap<String, CircuitManifest>,
+    bbfs: HashMap<String, BlackBoxFunction>,
+    memory_budget: Option<usize>,
+    #[cfg(feature = "reqwest-blocking")]
+    cache_dir: Option<PathBuf>,
+    loaded: Mutex<LoadedCircuits>,
+    /// Held while a circuit is loaded, one per circuit of the manifest.
+    loading: HashMap<String, Mutex<()>>,
+    #[cfg(test)]
+    num_loads: std::sync::atomic::AtomicUsize,
+}
+
+impl MaterialRegistry {
+    /// Creates a registry for the circuits of the manifest without loading them.
+    pub fn new(manifest: MaterialManifest) -> Result<Self, RegistryError> {
+        let mut manifests = HashMap::with_capacity(manifest.circuits.len());
+        for circuit in manifest.circuits {
+            if manifests.contains_key(&circuit.name) {
+                return Err(RegistryError::DuplicateCircuit(circuit.name));
+            }
+            manifests.insert(circuit.name.clone(), circuit);
+        }
+        let loading = manifests
+            .keys()
+            .map(|name| (name.clone(), Mutex::default()))
+            .collect();
+        Ok(Self {
+            manifests,
+            bbfs: HashMap::default(),
+            memory_budget: None,
+            #[cfg(feature = "reqwest-blocking")]
+            cache_dir: None,
+            loaded: Mutex::default(),
+            loading,
+            #[cfg(test)]
+            num_loads: Default::default(),
+        })
+    }
+
+    /// Sets the memory budget in bytes for the loaded circuits. By default, loaded circuits are never evicted.
+    pub fn memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Adds custom black-box functions that circuits of the manifest can refer to by name.
+    pub fn add_bbfs(mut self, bbfs: HashMap<String, BlackBoxFunction>) -> Self {
+        self.bbfs.extend(bbfs);
+        self
+    }
+
+    /// Sets the cache directory for circuits loaded from URLs. See [`CircomGroth16MaterialBuilder::cache_dir`].
+    #[cfg(feature = "reqwest-blocking")]
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Returns the names of all circuits in the manifest.
+    pub fn circuits(&self) -> impl Iterator<Item = &str> {
+        self.manifests.keys().map(String::as_str)
+    }
+
+    /// Returns the names of the currently loaded circuits.
+    pub fn loaded_circuits(&self) -> Vec<String> {
+        self.lock().circuits.keys().cloned().collect()
+    }
+
+    /// Returns the estimated memory usage of the loaded circuits in bytes.
+    pub fn loaded_size(&self) -> usize {
+        self.lock().size
+    }
+
+    /// Evicts a loaded circuit. Returns whether the circuit was loaded.
+    pub fn evict(&self, name: &str) -> bool {
+        self.lock().remove(name)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LoadedCircuits> {
+        // the state stays consistent even if a thread panicked while holding the lock
+        self.loaded
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns the material of the circuit and loads it if necessary.
+    pub fn material(&self, name: &str) -> Result<Arc<CircomGroth16Material>, RegistryError> {
+        if let Some(material) = self.lock().get(name) {
+            return Ok(material);
+        }
+        let manifest = self
+            .manifests
+            .get(name)
+            .ok_or_else(|| RegistryError::UnknownCircuit(name.to_owned()))?;
+        // load without holding the lock, so other circuits can be used in the meantime, but only once per circuit
+        let _loading = self.loading[name]
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(material) = self.lock().get(name) {
+            // loaded by another thread while waiting
+            return Ok(material);
+        }
+        let (material, size) = self.load(manifest)?;
+
+        let mut loaded = self.lock();
+        if let Some(memory_budget) = self.memory_budget {
+            while loaded.size + size > memory_budget {
+                let Some(lru) = loaded
+                    .circuits
+                    .iter()
+                    .min_by_key(|(_, circuit)| circuit.last_used)
+                    .map(|(name, _)| name.clone())
+                else {
+                    break;
+                };
+                loaded.remove(&lru);
+            }
+        }
+        let material = Arc::new(material);
+        loaded.clock += 1;
+        let last_used = loaded.clock;
+        loaded.size += size;
+        loaded.circuits.insert(
+            name.to_owned(),
+            LoadedCircuit {
+                material: Arc::clone(&material),
+                size,
+                last_used,
+            },
+        );
+        Ok(material)
+    }
+
+    fn load(
+        &self,
+        manifest: &CircuitManifest,
+    ) -> Result<(CircomGroth16Material, usize), RegistryError> {
+        #[cfg(test)]
+        self.num_loads
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut builder = CircomGroth16MaterialBuilder::new();
+        if let Some(fingerprint) = &manifest.fingerprint_zkey {
+            builder = builder.fingerprint_zkey(fingerprint.clone());
+        }
+        if let Some(fingerprint) = &manifest.fingerprint_graph {
+            builder = builder.fingerprint_graph(fingerprint.clone());
+        }
+        if let Some(names) = &manifest.public_signals {
+            builder = builder.public_signal_names(names.clone());
+        }
+        if !manifest.input_signals.is_empty() {
+            builder = builder.input_signal_names(manifest.input_signals.iter().cloned());
+        }
+        for bbf in &manifest.bbfs {
+            builder = match bbf.as_str() {
+                "bbf_inv" => builder.bbf_inv(),
+                "bbf_legendre" => builder.bbf_legendre(),
+                "bbf_sqrt_unchecked" => builder.bbf_sqrt_unchecked(),
+                "bbf_sqrt_input" => builder.bbf_sqrt_input(),
+                "bbf_num_2_bits_helper" => builder.bbf_num_2_bits_helper(),
+                name => {
+                    let function = self.bbfs.get(name).ok_or_else(|| {
+                        RegistryError::UnknownBlackBoxFunction {
+                            circuit: manifest.name.clone(),
+                            bbf: name.to_owned(),
+                        }
+                    })?;
+                    builder.add_bbfs(HashMap::from([(name.to_owned(), Arc::clone(function))]))
+                }
+            };
+        }
+        #[cfg(feature = "reqwest-blocking")]
+        if let Some(cache_dir) = &self.cache_dir {
+            builder = builder.cache_dir(cache_dir);
+        }
+        let load_error = |source| RegistryError::Load {
+            circuit: manifest.name.clone(),
+            source,
+        };
+        let zkey_bytes = Self::read_artifact(manifest, &manifest.zkey, &builder, Artifact::Zkey)?;
+        let graph_bytes =
+            Self::read_artifact(manifest, &manifest.graph, &builder, Artifact::Graph)?;
+        let size = zkey_bytes.len() + graph_bytes.len();
+        let material = builder
+            .build_from_bytes(&zkey_bytes, &graph_bytes)
+            .map_err(load_error)?;
+        Ok((material, size))
+    }
+
+    #[cfg_attr(not(feature = "reqwest-blocking"), allow(unused_variables))]
+    fn read_artifact(
+        manifest: &CircuitManifest,
+        source: &ArtifactSource,
+        builder: &CircomGroth16MaterialBuilder,
+        artifact: Artifact,
+    ) -> Result<Vec<u8>, RegistryError> {
+        let bytes = match source {
+            ArtifactSource::Path(path) => std::fs::read(path).map_err(ZkeyError::from),
+            #[cfg(feature = "reqwest-blocking")]
+            ArtifactSource::Url(url) => builder.download_blocking(artifact, url.as_str()),
+            #[cfg(not(feature = "reqwest-blocking"))]
+            ArtifactSource::Url(_) => {
+                return Err(RegistryError::UrlNotSupported(manifest.name.clone()));
+            }
+        };
+        bytes.map_err(|source| RegistryError::Load {
+            circuit: manifest.name.clone(),
+            source,
+        })
+    }
+
+    /// Generates a Groth16 proof for the circuit from structured inputs, see [`CircomGroth16Material::generate_proof`].
+    pub fn prove<R: Rng + CryptoRng>(
+        &self,
+        name: &str,
+        inputs: &impl ProofInput,
+        rng: &mut R,
+    ) -> Result<(Proof<Bn254>, Vec<ark_bn254::Fr>), RegistryError> {
+        Ok(self.material(name)?.generate_proof(inputs, rng)?)
+    }
+
+    /// Verifies a Groth16 proof for the circuit, see [`CircomGroth16Material::verify_proof`].
+    ///
+    /// Loads the circuit if necessary.
+    pub fn verify(
+        &self,
+        name: &str,
+        proof: &Proof<Bn254>,
+        public_inputs: &[ark_bn254::Fr],
+    ) -> Result<(), RegistryError> {
+        Ok(self.material(name)?.verify_proof(proof, public_inputs)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+
+    use super::super::test_utils::*;
+    use super::*;
+
+    /// Writes the graph into a temporary directory and returns a manifest of `circuits` with their black-box functions.
+    fn manifest(name: &str, circuits: &[(&str, &[&str])]) -> (MaterialManifest, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "groth16-material-registry-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let graph = dir.join("multiplier2.graph");
+        std::fs::write(&graph, graph_bytes()).unwrap();
+        let circuits = circuits
+            .iter()
+            .map(|(name, bbfs)| {
+                serde_json::json!({
+                    "name": name,
+                    "zkey": { "path": format!("{KATS}/circuit.zkey") },
+                    "graph": { "path": graph },
+                    "bbfs": bbfs,
+                    "public_signals": [{ "name": "c", "size": 1 }],
+                    "input_signals": ["a", "b"],
+                })
+            })
+            .collect::<Vec<_>>();
+        let manifest = serde_json::json!({ "circuits": circuits }).to_string();
+        (
+            MaterialManifest::from_reader(manifest.as_bytes()).unwrap(),
+            dir,
+        )
+    }
+
+    fn circuit_size() -> usize {
+        zkey_bytes().len() + graph_bytes().len()
+    }
+
+    fn sorted(mut names: Vec<String>) -> Vec<String> {
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        let (duplicate, dir) = manifest("invalid", &[("m", &[]), ("m", &[])]);
+        assert!(matches!(
+            MaterialRegistry::new(duplicate),
+            Err(RegistryError::DuplicateCircuit(name)) if name == "m"
+        ));
+
+        let custom = [("custom", ["bbf_inv", "bbf_custom"].as_slice())];
+        let registry = MaterialRegistry::new(manifest("invalid", &custom).0).unwrap();
+        assert!(matches!(
+            registry.material("unknown"),
+            Err(RegistryError::UnknownCircuit(name)) if name == "unknown"
+        ));
+        assert!(matches!(
+            registry.material("custom"),
+            Err(RegistryError::UnknownBlackBoxFunction { circuit, bbf })
+                if circuit == "custom" && bbf == "bbf_custom"
+        ));
+        assert!(registry.loaded_circuits().is_empty());
+
+        let bbf: BlackBoxFunction = Arc::new(|args: &[ark_bn254::Fr]| args[0]);
+        let registry = MaterialRegistry::new(manifest("invalid", &custom).0)
+            .unwrap()
+            .add_bbfs(HashMap::from([("bbf_custom".to_owned(), bbf)]));
+        registry.material("custom").unwrap();
+        assert_eq!(registry.loaded_circuits(), ["custom"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn loads_lazily_and_proves_by_name() {
+        let (manifest, dir) = manifest("lazy", &[("m1", &[]), ("m2", &[])]);
+        let registry = MaterialRegistry::new(manifest).unwrap();
+        assert_eq!(
+            sorted(registry.circuits().map(str::to_owned).collect()),
+            ["m1", "m2"]
+        );
+        assert!(registry.loaded_circuits().is_empty());
+        assert_eq!(registry.loaded_size(), 0);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let (proof, public_inputs) = registry.prove("m1", &inputs(3, 11), &mut rng).unwrap();
+        assert_eq!(registry.loaded_circuits(), ["m1"]);
+        assert_eq!(registry.loaded_size(), circuit_size());
+        registry.verify("m1", &proof, &public_inputs).unwrap();
+        // the manifest names the public signals
+        let material = registry.material("m1").unwrap();
+        assert_eq!(
+            material.public_signals(&public_inputs).unwrap().get("c"),
+            Some([ark_bn254::Fr::from(33)].as_slice())
+        );
+
+        // verifying loads the circuit as well
+        registry.verify("m2", &proof, &public_inputs).unwrap();
+        assert_eq!(sorted(registry.loaded_circuits()), ["m1", "m2"]);
+        assert!(matches!(
+            registry.verify("m2", &proof, &[ark_bn254::Fr::from(34)]),
+            Err(RegistryError::Groth16(Groth16Error::InvalidProof))
+        ));
+        assert!(matches!(
+            registry.prove(
+                "m1",
+                &inputs(3, 11).into_iter().take(1).collect::<HashMap<_, _>>(),
+                &mut rng
+            ),
+            Err(RegistryError::Groth16(Groth16Error::InvalidInputs(err)))
+                if err.missing.len() == 1 && err.missing[0].name.is_some()
+        ));
+
+        assert!(registry.evict("m1"));
+        assert!(!registry.evict("m1"));
+        assert_eq!(registry.loaded_circuits(), ["m2"]);
+        assert_eq!(registry.loaded_size(), circuit_size());
+        assert!(registry.evict("m2"));
+        assert_eq!(registry.loaded_size(), 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let (manifest, dir) = manifest("lru", &[("m1", &[]), ("m2", &[]), ("m3", &[])]);
+        let registry = MaterialRegistry::new(manifest)
+            .unwrap()
+            .memory_budget(2 * circuit_size());
+        registry.material("m1").unwrap();
+        registry.material("m2").unwrap();
+        // m2 is now the least recently used circuit
+        let m1 = registry.material("m1").unwrap();
+        registry.material("m3").unwrap();
+        assert_eq!(sorted(registry.loaded_circuits()), ["m1", "m3"]);
+        assert_eq!(registry.loaded_size(), 2 * circuit_size());
+
+        registry.material("m2").unwrap();
+        assert_eq!(sorted(registry.loaded_circuits()), ["m2", "m3"]);
+        // evicted material stays usable while it is referenced
+        let (proof, public_inputs) = m1
+            .generate_proof(&inputs(3, 11), &mut rand::thread_rng())
+            .unwrap();
+        m1.verify_proof(&proof, &public_inputs).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn loads_oversize_circuit() {
+        let (manifest, dir) = manifest("oversize", &[("m1", &[]), ("m2", &[])]);
+        let registry = MaterialRegistry::new(manifest)
+            .unwrap()
+            .memory_budget(circuit_size() - 1);
+        registry.material("m1").unwrap();
+        assert_eq!(registry.loaded_circuits(), ["m1"]);
+        assert_eq!(registry.loaded_size(), circuit_size());
+        registry.material("m2").unwrap();
+        assert_eq!(registry.loaded_circuits(), ["m2"]);
+        assert_eq!(registry.loaded_size(), circuit_size());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn loads_concurrently_requested_circuit_once() {
+        let (manifest, dir) = manifest("concurrent", &[("m1", &[]), ("m2", &[])]);
+        let registry = MaterialRegistry::new(manifest).unwrap();
+        let barrier = std::sync::Barrier::new(8);
+        let materials = std::thread::scope(|scope| {
+            let handles = (0..8)
+                .map(|i| {
+                    let (registry, barrier) = (&registry, &barrier);
+                    scope.spawn(move || {
+                        barrier.wait();
+                        registry.material(if i < 6 { "m1" } else { "m2" }).unwrap()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            registry
+                .num_loads
+                .load(std::sync::atomic::Ordering::Relaxed),
+            2
+        );
+        assert!(
+            materials[..6]
+                .iter()
+                .all(|material| Arc::ptr_eq(material, &materials[0]))
+        );
+        assert!(Arc::ptr_eq(&materials[6], &materials[7]));
+        assert_eq!(registry.loaded_size(), 2 * circuit_size());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+//! The `multiplier2` circuit of the known-answer tests of `taceo-circom-types` (`c <== a * b`).
+
+use std::collections::HashMap;
+
+use circom_witness_rs::HashSignalInfo;
+use circom_witness_rs::graph::{Node, Operation};
+use ruint::aliases::U256;
+
+use super::{CircomGroth16Material, CircomGroth16MaterialBuilder, signal_hash};
+
+/// The directory of the BN254 Groth16 known-answer tests.
+pub(super) const KATS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../circom-types/kats/groth16/bn254"
+);
+
+/// Reads a file of the known-answer tests.
+pub(super) fn kat(name: &str) -> Vec<u8> {
+    std::fs::read(format!("{KATS}/{name}")).unwrap()
+}
+
+/// The `.zkey` generated by snarkjs.
+pub(super) fn zkey_bytes() -> Vec<u8> {
+    kat("circuit.zkey")
+}
+
+/// The witness graph of `multiplier2`, with the witness `[1, c, a, b]` of the Circom compiler.
+pub(super) fn graph_bytes() -> Vec<u8> {
+    let nodes = vec![
+        Node::Input(0),
+        Node::Input(2),
+        Node::Input(3),
+        Node::Op(Operation::Mul, 1, 2),
+    ];
+    let signals = vec![0usize, 3, 1, 2];
+    let input_mapping = [("a", 2), ("b", 3)]
+        .map(|(name, signalid)| HashSignalInfo {
+            hash: signal_hash(name),
+            signalid,
+            signalsize: 1,
+        })
+        .to_vec();
+    postcard::to_stdvec(&(nodes, signals, input_mapping)).unwrap()
+}
+
+/// The inputs `a` and `b`.
+pub(super) fn inputs(a: u64, b: u64) -> HashMap<String, Vec<U256>> {
+    HashMap::from([
+        ("a".to_owned(), vec![U256::from(a)]),
+        ("b".to_owned(), vec![U256::from(b)]),
+    ])
+}
+
+/// Loads the material from the `.zkey` and the witness graph.
+pub(super) fn material() -> CircomGroth16Material {
+    CircomGroth16MaterialBuilder::new()
+        .build_from_bytes(&zkey_bytes(), &graph_bytes())
+        .unwrap()
+}
+
+/// A `.wasm` witness calculator of `multiplier2`, written by hand with the ABI of Circom 2.
+#[cfg(feature = "wasm-witness")]
+pub(super) fn wasm_bytes() -> Vec<u8> {
+    let wat = include_str!("../../test-vectors/multiplier2.wat");
+    wat::parse_str(wat).unwrap()
+}
@@ -0,0 +1,297 @@
+//! Validation of proof inputs against the input signals of a witness graph.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use ark_ff::PrimeField as _;
+use circom_witness_rs::Graph;
+use ruint::aliases::U256;
+
+/// An input signal of the circuit without a value.
+///
+/// The graph only contains the FNV-1a hash of the signal names, see [`signal_hash`]. The name is resolved from the names
+/// set with [`CircomGroth16MaterialBuilder::input_signal_names`](super::CircomGroth16MaterialBuilder::input_signal_names)
+/// and the public signal names, if known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSignal {
+    /// The name of the signal, if known.
+    pub name: Option<String>,
+    /// The FNV-1a hash of the signal name.
+    pub hash: u64,
+    /// The number of values of the signal.
+    pub size: usize,
+}
+
+/// An input signal with the wrong number of values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrongLengthSignal {
+    /// The name of the signal.
+    pub name: String,
+    /// The number of values of the signal in the circuit.
+    pub expected: usize,
+    /// The number of values in the inputs.
+    pub actual: usize,
+}
+
+/// An input value that is not smaller than the modulus of the BN254 scalar field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutOfRangeValue {
+    /// The name of the signal.
+    pub name: String,
+    /// The index of the value in the signal.
+    pub index: usize,
+}
+
+/// Inputs that do not match the input signals of the circuit, see [`CircomGroth16Material::validate_inputs`](super::CircomGroth16Material::validate_inputs).
+///
+/// Lists all problems of the inputs at once, sorted by signal name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, thiserror::Error)]
+pub struct InputValidationError {
+    /// The input signals of the circuit without a value.
+    pub missing: Vec<MissingSignal>,
+    /// The inputs that are not input signals of the circuit.
+    pub unknown: Vec<String>,
+    /// The input signals with the wrong number of values.
+    pub wrong_length: Vec<WrongLengthSignal>,
+    /// The values that are not smaller than the field modulus.
+    pub out_of_range: Vec<OutOfRangeValue>,
+}
+
+impl fmt::Display for InputValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut problems = Vec::new();
+        problems.extend(self.missing.iter().map(|signal| match &signal.name {
+            Some(name) => format!("missing signal {name} ({} values)", signal.size),
+            None => format!(
+                "missing signal with hash {:#018x} ({} values)",
+                signal.hash, signal.size
+            ),
+        }));
+        problems.extend(
+            self.unknown
+                .iter()
+                .map(|name| format!("unknown signal {name}")),
+        );
+        problems.extend(self.wrong_length.iter().map(|signal| {
+            format!(
+                "signal {} expects {} values, got {}",
+                signal.name, signal.expected, signal.actual
+            )
+        }));
+        problems.extend(self.out_of_range.iter().map(|value| {
+            format!(
+                "value {} of signal {} exceeds the field modulus",
+                value.index, value.name
+            )
+        }));
+        write!(f, "invalid inputs: {}", problems.join(", "))
+    }
+}
+
+impl InputValidationError {
+    fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.unknown.is_empty()
+            && self.wrong_length.is_empty()
+            && self.out_of_range.is_empty()
+    }
+}
+
+/// Returns the FNV-1a hash of an input signal name, as used by the witness graph and the Circom witness calculator.
+pub fn signal_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Sorts the inputs by name for a deterministic order of the problems.
+fn sorted_inputs(inputs: &HashMap<String, Vec<U256>>) -> Vec<(&String, &Vec<U256>)> {
+    let mut inputs = inputs.iter().collect::<Vec<_>>();
+    inputs.sort_unstable_by_key(|(name, _)| *name);
+    inputs
+}
+
+/// Checks that all values are smaller than the field modulus.
+fn check_range(inputs: &HashMap<String, Vec<U256>>, error: &mut InputValidationError) {
+    let modulus = U256::from_limbs(ark_bn254::Fr::MODULUS.0);
+    for (name, values) in sorted_inputs(inputs) {
+        error.out_of_range.extend(
+            values
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value >= modulus)
+                .map(|(index, _)| OutOfRangeValue {
+                    name: name.clone(),
+                    index,
+                }),
+        );
+    }
+}
+
+/// Checks the inputs against the `input_mapping` of the graph and the field modulus.
+///
+/// The names of missing signals are resolved from `names`.
+pub(super) fn validate_graph_inputs(
+    graph: &Graph,
+    inputs: &HashMap<String, Vec<U256>>,
+    names: &[String],
+) -> Result<(), InputValidationError> {
+    let mut error = InputValidationError::default();
+    let mut provided = Vec::with_capacity(inputs.len());
+    for (name, values) in sorted_inputs(inputs) {
+        let hash = signal_hash(name);
+        match graph.input_mapping.iter().find(|info| info.hash == hash) {
+            Some(info) => {
+                provided.push(hash);
+                if values.len() as u64 != info.signalsize {
+                    error.wrong_length.push(WrongLengthSignal {
+                        name: name.clone(),
+                        expected: info.signalsize as usize,
+                        actual: values.len(),
+                    });
+                }
+            }
+            None => error.unknown.push(name.clone()),
+        }
+    }
+    error.missing = graph
+        .input_mapping
+        .iter()
+        .filter(|info| !provided.contains(&info.hash))
+        .map(|info| MissingSignal {
+            name: names
+                .iter()
+                .find(|name| signal_hash(name) == info.hash)
+                .cloned(),
+            hash: info.hash,
+            size: info.signalsize as usize,
+        })
+        .collect();
+    check_range(inputs, &mut error);
+    if error.is_empty() { Ok(()) } else { Err(error) }
+}
+
+/// Checks that all values are smaller than the field modulus.
+#[cfg(feature = "wasm-witness")]
+pub(super) fn validate_range(
+    inputs: &HashMap<String, Vec<U256>>,
+) -> Result<(), InputValidationError> {
+    let mut error = InputValidationError::default();
+    check_range(inputs, &mut error);
+    if error.is_empty() { Ok(()) } else { Err(error) }
+}
+
+#[cfg(test)]
+mod tests {
+    use circom_witness_rs::HashSignalInfo;
+
+    use super::super::CircomGroth16MaterialBuilder;
+    use super::super::test_utils;
+    use super::*;
+    use crate::Groth16Error;
+
+    fn graph(signals: &[(&str, u64)]) -> Graph {
+        Graph {
+            nodes: Vec::new(),
+            signals: Vec::new(),
+            input_mapping: signals
+                .iter()
+                .enumerate()
+                .map(|(idx, (name, size))| HashSignalInfo {
+                    hash: signal_hash(name),
+                    signalid: idx as u64 + 1,
+                    signalsize: *size,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn reports_all_problems() {
+        let graph = graph(&[("a", 1), ("b", 2), ("c", 3)]);
+        let modulus = U256::from_limbs(ark_bn254::Fr::MODULUS.0);
+        let inputs = HashMap::from([
+            ("a".to_owned(), vec![modulus]),
+            ("b".to_owned(), vec![U256::from(1)]),
+            ("d".to_owned(), vec![U256::from(1), modulus - U256::from(1)]),
+        ]);
+        let error = validate_graph_inputs(&graph, &inputs, &[]).unwrap_err();
+        assert_eq!(
+            error,
+            InputValidationError {
+                missing: vec![MissingSignal {
+                    name: None,
+                    hash: signal_hash("c"),
+                    size: 3
+                }],
+                unknown: vec!["d".to_owned()],
+                wrong_length: vec![WrongLengthSignal {
+                    name: "b".to_owned(),
+                    expected: 2,
+                    actual: 1
+                }],
+                out_of_range: vec![OutOfRangeValue {
+                    name: "a".to_owned(),
+                    index: 0
+                }],
+            }
+        );
+
+        let inputs = HashMap::from([
+            ("a".to_owned(), vec![U256::from(1)]),
+            ("b".to_owned(), vec![U256::from(2); 2]),
+            ("c".to_owned(), vec![U256::from(3); 3]),
+        ]);
+        assert_eq!(validate_graph_inputs(&graph, &inputs, &[]), Ok(()));
+    }
+
+    #[test]
+    fn material_reports_missing_signals_by_name() {
+        let modulus = U256::from_limbs(ark_bn254::Fr::MODULUS.0);
+        let inputs = HashMap::from([
+            ("b".to_owned(), vec![modulus, U256::from(1)]),
+            ("x".to_owned(), vec![U256::from(1)]),
+        ]);
+        let unnamed = test_utils::material().validate_inputs(&inputs).unwrap_err();
+        assert_eq!(unnamed.missing[0].name, None);
+        assert!(unnamed.to_string().contains(&format!(
+            "missing signal with hash {:#018x} (1 values)",
+            signal_hash("a")
+        )));
+
+        let material = CircomGroth16MaterialBuilder::new()
+            .input_signal_names(["a", "b"])
+            .build_from_bytes(&test_utils::zkey_bytes(), &test_utils::graph_bytes())
+            .unwrap();
+        let error = material.validate_inputs(&inputs).unwrap_err();
+        assert_eq!(
+            error,
+            InputValidationError {
+                missing: vec![MissingSignal {
+                    name: Some("a".to_owned()),
+                    hash: signal_hash("a"),
+                    size: 1
+                }],
+                unknown: vec!["x".to_owned()],
+                wrong_length: vec![WrongLengthSignal {
+                    name: "b".to_owned(),
+                    expected: 1,
+                    actual: 2
+                }],
+                out_of_range: vec![OutOfRangeValue {
+                    name: "b".to_owned(),
+                    index: 0
+                }],
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid inputs: missing signal a (1 values), unknown signal x, signal b expects 1 values, got 2, \
+             value 0 of signal b exceeds the field modulus"
+        );
+        assert!(matches!(
+            material.generate_witness(&inputs),
+            Err(Groth16Error::InvalidInputs(err)) if err == error
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+//! Witness generation with the `.wasm` witness calculator generated by the Circom compiler.
+//!
+//! Follows the `witness_calculator.js` of Circom 2: the inputs are written into the shared memory of the module one field
+//! element at a time, identified by the FNV-1a hash of their name, and the witness is read back the same way.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ark_ff::{BigInt, PrimeField as _};
+use ruint::aliases::U256;
+use wasmi::{Caller, Engine, Extern, Instance, Linker, Module, Store, TypedFunc};
+
+/// The state of the host functions during one witness calculation.
+#[derive(Default)]
+struct HostState {
+    /// The error messages of the circuit, reported before an exception.
+    error: String,
+}
+
+/// A Circom `.wasm` witness calculator, executed with the `wasmi` interpreter.
+#[derive(Clone)]
+pub(super) struct WasmWitnessCalculator {
+    wasm: Arc<[u8]>,
+    engine: Engine,
+    module: Arc<Module>,
+    /// The number of 32-bit words of a field element.
+    n32: usize,
+}
+
+fn wasm_error(err: wasmi::Error) -> eyre::Report {
+    eyre::eyre!("wasm witness calculator failed: {err}")
+}
+
+/// Reads a message of the module character by character.
+fn read_message(caller: &mut Caller<'_, HostState>) -> Result<String, wasmi::Error> {
+    let get_message_char = caller
+        .get_export("getMessageChar")
+        .and_then(Extern::into_func)
+        .ok_or_else(|| wasmi::Error::new("missing export getMessageChar"))?
+        .typed::<(), i32>(&*caller)?;
+    let mut message = String::new();
+    loop {
+        let c = get_message_char.call(&mut *caller, ())?;
+        if c == 0 {
+            return Ok(message);
+        }
+        message.push(char::from(c as u8));
+    }
+}
+
+/// Splits the FNV-1a hash of an input name into its most and least significant 32 bits.
+fn fnv1a(name: &str) -> (i32, i32) {
+    let hash = super::signal_hash(name);
+    ((hash >> 32) as u32 as i32, hash as u32 as i32)
+}
+
+impl WasmWitnessCalculator {
+    /// Compiles the module and checks that it was generated by Circom 2 for the BN254 scalar field.
+    pub(super) fn new(wasm: &[u8]) -> eyre::Result<Self> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, wasm).map_err(wasm_error)?;
+        let mut calculator = Self {
+            wasm: Arc::from(wasm),
+            engine,
+            module: Arc::new(module),
+            n32: 0,
+        };
+
+        let mut store = Store::new(&calculator.engine, HostState::default());
+        let instance = calculator.instantiate(&mut store)?;
+        let version = Self::export::<(), i32>(&instance, &store, "getVersion")?
+            .call(&mut store, ())
+            .map_err(wasm_error)?;
+        if version < 2 {
+            eyre::bail!("unsupported witness calculator version {version}, expected Circom 2");
+        }
+        calculator.n32 = Self::export::<(), i32>(&instance, &store, "getFieldNumLen32")?
+            .call(&mut store, ())
+            .map_err(wasm_error)? as usize;
+        Self::export::<(), ()>(&instance, &store, "getRawPrime")?
+            .call(&mut store, ())
+            .map_err(wasm_error)?;
+        let prime = calculator.read_shared_memory(&instance, &mut store)?;
+        if prime != ark_bn254::Fr::MODULUS {
+            eyre::bail!("witness calculator is not compiled for the bn254 scalar field");
+        }
+        Ok(calculator)
+    }
+
+    /// Returns the bytes of the module.
+    pub(super) fn wasm(&self) -> &[u8] {
+        &self.wasm
+    }
+
+    fn instantiate(&self, store: &mut Store<HostState>) -> eyre::Result<Instance> {
+        let mut linker = Linker::<HostState>::new(&self.engine);
+        linker
+            .func_wrap(
+                "runtime",
+                "exceptionHandler",
+                |caller: Caller<'_, HostState>, code: i32| -> Result<(), wasmi::Error> {
+                    let reason = match code {
+                        1 => "signal not found",
+                        2 => "too many signals set",
+                        3 => "signal already set",
+                        4 => "assert failed",
+                        5 => "not enough memory",
+                        6 => "input signal array access exceeds the size",
+                        _ => "unknown error",
+                    };
+                    Err(wasmi::Error::new(format!(
+                        "{reason} (code {code}): {}",
+                        caller.data().error.trim_end()
+                    )))
+                },
+            )
+            .map_err(|err| eyre::eyre!("{err}"))?
+            .func_wrap(
+                "runtime",
+                "printErrorMessage",
+                |mut caller: Caller<'_, HostState>| -> Result<(), wasmi::Error> {
+                    let message = read_message(&mut caller)?;
+                    let error = &mut caller.data_mut().error;
+                    error.push_str(&message);
+                    error.push('\n');
+                    Ok(())
+                },
+            )
+            .map_err(|err| eyre::eyre!("{err}"))?
+            // the output of `log` in the circuit is discarded
+            .func_wrap(
+                "runtime",
+                "writeBufferMessage",
+                |_: Caller<'_, HostState>| {},
+            )
+            .map_err(|err| eyre::eyre!("{err}"))?
+            .func_wrap(
+                "runtime",
+                "showSharedRWMemory",
+                |_: Caller<'_, HostState>| {},
+            )
+            .map_err(|err| eyre::eyre!("{err}"))?;
+        linker
+            .instantiate(&mut *store, &self.module)
+            .and_then(|instance| instance.start(&mut *store))
+            .map_err(wasm_error)
+    }
+
+    fn export<Params: wasmi::WasmParams, Results: wasmi::WasmResults>(
+        instance: &Instance,
+        store: &Store<HostState>,
+        name: &str,
+    ) -> eyre::Result<TypedFunc<Params, Results>> {
+        instance
+            .get_func(store, name)
+            .ok_or_else(|| eyre::eyre!("witness calculator does not export {name}"))?
+            .typed(store)
+            .map_err(wasm_error)
+    }
+
+    /// Reads a field element from the shared memory.
+    fn read_shared_memory(
+        &self,
+        instance: &Instance,
+        store: &mut Store<HostState>,
+    ) -> eyre::Result<BigInt<4>> {
+        let read = Self::export::<i32, i32>(instance, store, "readSharedRWMemory")?;
+        let mut limbs = [0u64; 4];
+        for j in 0..self.n32.min(8) {
+            let word = read.call(&mut *store, j as i32).map_err(wasm_error)? as u32;
+            limbs[j / 2] |= u64::from(word) << (32 * (j % 2));
+        }
+        Ok(BigInt(limbs))
+    }
+
+    /// Computes the witness for the inputs.
+    pub(super) fn calculate_witness(
+        &self,
+        inputs: HashMap<String, Vec<U256>>,
+    ) -> eyre::Result<Vec<ark_bn254::Fr>> {
+        let mut store = Store::new(&self.engine, HostState::default());
+        let instance = self.instantiate(&mut store)?;
+        let write = Self::export::<(i32, i32), ()>(&instance, &store, "writeSharedRWMemory")?;
+        let set_input_signal =
+            Self::export::<(i32, i32, i32), ()>(&instance, &store, "setInputSignal")?;
+        let get_input_signal_size =
+            Self::export::<(i32, i32), i32>(&instance, &store, "getInputSignalSize")?;
+
+        // enable the sanity checks of the circuit
+        Self::export::<i32, ()>(&instance, &store, "init")?
+            .call(&mut store, 1)
+            .map_err(wasm_error)?;
+
+        let mut num_inputs = 0;
+        for (name, values) in inputs {
+            let (msb, lsb) = fnv1a(&name);
+            let size = get_input_signal_size
+                .call(&mut store, (msb, lsb))
+                .map_err(wasm_error)?;
+            if size < 0 {
+                eyre::bail!("signal {name} not found");
+            }
+            if values.len() != size as usize {
+                eyre::bail!("signal {name} expects {size} values, got {}", values.len());
+            }
+            for (idx, value) in values.iter().enumerate() {
+                let value = ark_bn254::Fr::from_le_bytes_mod_order(&value.to_le_bytes::<32>())
+                    .into_bigint();
+                for j in 0..self.n32 {
+                    let word = value.0.get(j / 2).map_or(0, |limb| limb >> (32 * (j % 2)));
+                    write
+                        .call(&mut store, (j as i32, word as u32 as i32))
+                        .map_err(wasm_error)?;
+                }
+                set_input_signal
+                    .call(&mut store, (msb, lsb, idx as i32))
+                    .map_err(wasm_error)?;
+                num_inputs += 1;
+            }
+        }
+        let expected_inputs = Self::export::<(), i32>(&instance, &store, "getInputSize")?
+            .call(&mut store, ())
+            .map_err(wasm_error)?;
+        if num_inputs < expected_inputs {
+            eyre::bail!("not all inputs have been set, only {num_inputs} out of {expected_inputs}");
+        }
+
+        let get_witness = Self::export::<i32, ()>(&instance, &store, "getWitness")?;
+        let witness_size = Self::export::<(), i32>(&instance, &store, "getWitnessSize")?
+            .call(&mut store, ())
+            .map_err(wasm_error)?;
+        (0..witness_size)
+            .map(|idx| {
+                get_witness.call(&mut store, idx).map_err(wasm_error)?;
+                let value = self.read_shared_memory(&instance, &mut store)?;
+                ark_bn254::Fr::from_bigint(value)
+                    .ok_or_else(|| eyre::eyre!("witness {idx} is not a field element"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Field as _;
+
+    use super::super::test_utils::*;
+    use super::super::{CircomGroth16MaterialBuilder, WitnessCalculatorFormat};
+    use super::*;
+
+    fn calculator() -> WasmWitnessCalculator {
+        WasmWitnessCalculator::new(&wasm_bytes()).unwrap()
+    }
+
+    #[test]
+    fn matches_graph_and_kat_witness() {
+        let witness = calculator().calculate_witness(inputs(3, 11)).unwrap();
+        let kat =
+            circom_types::Witness::<ark_bn254::Fr>::from_reader(kat("witness.wtns").as_slice())
+                .unwrap();
+        assert_eq!(witness, kat.values);
+        assert_eq!(
+            witness,
+            material().generate_witness(&inputs(3, 11)).unwrap()
+        );
+
+        let material = CircomGroth16MaterialBuilder::new()
+            .build_from_bytes(&zkey_bytes(), &wasm_bytes())
+            .unwrap();
+        assert_eq!(
+            WitnessCalculatorFormat::Auto.detect(&wasm_bytes()),
+            WitnessCalculatorFormat::Wasm
+        );
+        let (proof, public_inputs) = material
+            .generate_proof(&inputs(3, 11), &mut rand::thread_rng())
+            .unwrap();
+        material.verify_proof(&proof, &public_inputs).unwrap();
+        assert_eq!(material.serializer().to_bytes().unwrap().1, wasm_bytes());
+    }
+
+    #[test]
+    fn packs_field_elements_into_words() {
+        let max = U256::from_limbs(ark_bn254::Fr::MODULUS.0) - U256::from(1);
+        let inputs = HashMap::from([
+            ("a".to_owned(), vec![max]),
+            ("b".to_owned(), vec![U256::from(1) << 200 | U256::from(5)]),
+        ]);
+        let witness = calculator().calculate_witness(inputs).unwrap();
+        assert_eq!(witness[2], -ark_bn254::Fr::ONE);
+        assert_eq!(
+            witness[3],
+            ark_bn254::Fr::from(2).pow([200]) + ark_bn254::Fr::from(5)
+        );
+    }
+
+    #[test]
+    fn rejects_other_primes() {
+        let wat = include_str!("../../test-vectors/multiplier2.wat")
+            .replace("0x43e1f593f0000001", "0x43e1f593f0000003");
+        let err = WasmWitnessCalculator::new(&wat::parse_str(wat).unwrap())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("bn254 scalar field"), "{err}");
+        assert!(WasmWitnessCalculator::new(b"\0asm").is_err());
+    }
+
+    #[test]
+    fn reports_errors() {
+        let calculator = calculator();
+        let error = |inputs: HashMap<String, Vec<U256>>| {
+            calculator
+                .calculate_witness(inputs)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let mut unknown = inputs(3, 11);
+        unknown.insert("c".to_owned(), vec![U256::from(33)]);
+        assert_eq!(error(unknown), "signal c not found");
+
+        let mut wrong_length = inputs(3, 11);
+        wrong_length.insert("a".to_owned(), vec![U256::from(3); 2]);
+        assert_eq!(error(wrong_length), "signal a expects 1 values, got 2");
+
+        let mut too_few = inputs(3, 11);
+        too_few.remove("b");
+        assert_eq!(
+            error(too_few),
+            "not all inputs have been set, only 1 out of 2"
+        );
+
+        let assert_failed = error(inputs(3, 0));
+        assert!(
+            assert_failed.contains("assert failed (code 4)")
+                && assert_failed.contains("Error in template Multiplier2_0 line: 9"),
+            "{assert_failed}"
+        );
+    }
+}
@@ -5,6 +5,10 @@
 //! Currently, it supports circuits defined using Circom.
 #![deny(missing_docs)]
 
+// the code generated by `#[derive(ProofInput)]` refers to this crate by name
+#[cfg(feature = "derive")]
+extern crate self as taceo_groth16_material;
+
 #[cfg(feature = "circom")]
 pub mod circom;
 
@@ -14,9 +18,41 @@ pub enum Groth16Error {
     /// Failed to generate a witness for the circuit.
     #[error("failed to generate witness")]
     WitnessGeneration(#[source] eyre::Report),
+    /// The inputs do not match the input signals of the circuit.
+    #[cfg(feature = "circom")]
+    #[error(transparent)]
+    InvalidInputs(circom::InputValidationError),
+    /// Could not parse a `.wtns` witness file, e.g., because it is not over the BN254 scalar field.
+    #[error("could not parse witness file")]
+    WitnessParsing(#[source] circom_types::WitnessParserError),
+    /// The witness does not have the number of variables of the proving key.
+    #[error("invalid witness length - expected {expected} values, got {actual}")]
+    InvalidWitnessLength {
+        /// The number of variables of the proving key.
+        expected: usize,
+        /// The number of values of the witness.
+        actual: usize,
+    },
+    /// The first value of the witness (the constant one) is not one.
+    #[error("invalid witness - the first value must be one")]
+    InvalidWitnessConstant,
     /// Failed to generate a Groth16 proof.
     #[error("failed to generate proof")]
-    ProofGeneration(#[source] eyre::Report),
+    ProofGeneration(#[source] groth16::Groth16Error),
+    /// The public inputs do not match the verification key.
+    #[error("malformed public inputs")]
+    MalformedPublicInputs(#[source] groth16::Groth16Error),
+    /// The number of public inputs does not match the public signal names of the circuit.
+    #[error("invalid public inputs length - expected {expected} values, got {actual}")]
+    InvalidPublicInputsLength {
+        /// The number of values of the public signals.
+        expected: usize,
+        /// The number of public inputs.
+        actual: usize,
+    },
+    /// The material was built without public signal names.
+    #[error("no public signal names set for the circuit")]
+    MissingPublicSignalNames,
     /// Generated proof could not be verified against the verification key.
     #[error("proof could not be verified")]
     InvalidProof,
@@ -0,0 +1,112 @@
+;; A witness calculator for the `multiplier2` circuit of the known-answer tests with the ABI of the
+;; `.wasm` files generated by Circom 2:
+;;
+;;     template Multiplier2() {
+;;         signal input a;
+;;         signal input b;
+;;         signal output c;
+;;         assert(b != 0);
+;;         c <== a * b;
+;;     }
+;;
+;; The witness is `[1, c, a, b]`. The inputs are stored as full field elements, but `c` is only the
+;; product of the lowest 64 bits of `a` and `b`, which is correct as long as it fits into 64 bits.
+(module
+  (import "runtime" "exceptionHandler" (func $exceptionHandler (param i32)))
+  (import "runtime" "printErrorMessage" (func $printErrorMessage))
+  (import "runtime" "writeBufferMessage" (func $writeBufferMessage))
+  (import "runtime" "showSharedRWMemory" (func $showSharedRWMemory))
+
+  ;; 0..32: shared read/write memory, 64..96: signal a, 96..128: signal b, 128..: error message
+  (memory (export "memory") 1)
+  (data (i32.const 128) "Error in template Multiplier2_0 line: 9\00")
+
+  (global $numSet (mut i32) (i32.const 0))
+  (global $messagePos (mut i32) (i32.const 128))
+
+  (func $copy (param $dst i32) (param $src i32)
+    (i64.store (local.get $dst) (i64.load (local.get $src)))
+    (i64.store offset=8 (local.get $dst) (i64.load offset=8 (local.get $src)))
+    (i64.store offset=16 (local.get $dst) (i64.load offset=16 (local.get $src)))
+    (i64.store offset=24 (local.get $dst) (i64.load offset=24 (local.get $src))))
+
+  (func $clear
+    (i64.store (i32.const 0) (i64.const 0))
+    (i64.store (i32.const 8) (i64.const 0))
+    (i64.store (i32.const 16) (i64.const 0))
+    (i64.store (i32.const 24) (i64.const 0)))
+
+  ;; the address of the input signal with the FNV-1a hash msb:lsb, or 0
+  (func $signalAddress (param $msb i32) (param $lsb i32) (result i32)
+    ;; "a"
+    (if (i32.and (i32.eq (local.get $msb) (i32.const 0xaf63dc4c))
+                 (i32.eq (local.get $lsb) (i32.const 0x8601ec8c)))
+      (then (return (i32.const 64))))
+    ;; "b"
+    (if (i32.and (i32.eq (local.get $msb) (i32.const 0xaf63df4c))
+                 (i32.eq (local.get $lsb) (i32.const 0x8601f1a5)))
+      (then (return (i32.const 96))))
+    (i32.const 0))
+
+  (func (export "getVersion") (result i32) (i32.const 2))
+  (func (export "getMinorVersion") (result i32) (i32.const 1))
+  (func (export "getPatchVersion") (result i32) (i32.const 6))
+  (func (export "getFieldNumLen32") (result i32) (i32.const 8))
+
+  (func (export "getRawPrime")
+    (i64.store (i32.const 0) (i64.const 0x43e1f593f0000001))
+    (i64.store (i32.const 8) (i64.const 0x2833e84879b97091))
+    (i64.store (i32.const 16) (i64.const 0xb85045b68181585d))
+    (i64.store (i32.const 24) (i64.const 0x30644e72e131a029)))
+
+  (func (export "readSharedRWMemory") (param $idx i32) (result i32)
+    (i32.load (i32.shl (local.get $idx) (i32.const 2))))
+
+  (func (export "writeSharedRWMemory") (param $idx i32) (param $value i32)
+    (i32.store (i32.shl (local.get $idx) (i32.const 2)) (local.get $value)))
+
+  (func (export "init") (param $sanityCheck i32)
+    (global.set $numSet (i32.const 0)))
+
+  (func (export "getInputSize") (result i32) (i32.const 2))
+  (func (export "getWitnessSize") (result i32) (i32.const 4))
+
+  (func (export "getMessageChar") (result i32)
+    (local $c i32)
+    (local.set $c (i32.load8_u (global.get $messagePos)))
+    (if (local.get $c)
+      (then (global.set $messagePos (i32.add (global.get $messagePos) (i32.const 1)))))
+    (local.get $c))
+
+  (func (export "getInputSignalSize") (param $msb i32) (param $lsb i32) (result i32)
+    (if (result i32) (call $signalAddress (local.get $msb) (local.get $lsb))
+      (then (i32.const 1))
+      (else (i32.const -1))))
+
+  (func (export "setInputSignal") (param $msb i32) (param $lsb i32) (param $idx i32)
+    (local $address i32)
+    (local.set $address (call $signalAddress (local.get $msb) (local.get $lsb)))
+    (if (i32.eqz (local.get $address))
+      (then (call $exceptionHandler (i32.const 1)) (return)))
+    (if (local.get $idx)
+      (then (call $exceptionHandler (i32.const 6)) (return)))
+    (call $copy (local.get $address) (i32.const 0))
+    (global.set $numSet (i32.add (global.get $numSet) (i32.const 1)))
+    ;; all inputs are set, run the circuit
+    (if (i32.eq (global.get $numSet) (i32.const 2))
+      (then
+        (if (i64.eqz (i64.or (i64.or (i64.load (i32.const 96)) (i64.load (i32.const 104)))
+                             (i64.or (i64.load (i32.const 112)) (i64.load (i32.const 120)))))
+          (then (call $printErrorMessage) (call $exceptionHandler (i32.const 4)))))))
+
+  (func (export "getWitness") (param $idx i32)
+    (call $clear)
+    (if (i32.eq (local.get $idx) (i32.const 0))
+      (then (i64.store (i32.const 0) (i64.const 1))))
+    (if (i32.eq (local.get $idx) (i32.const 1))
+      (then (i64.store (i32.const 0) (i64.mul (i64.load (i32.const 64)) (i64.load (i32.const 96))))))
+    (if (i32.eq (local.get $idx) (i32.const 2))
+      (then (call $copy (i32.const 0) (i32.const 64))))
+    (if (i32.eq (local.get $idx) (i32.const 3))
+      (then (call $copy (i32.const 0) (i32.const 96)))))
+)
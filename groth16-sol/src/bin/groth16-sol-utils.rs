@@ -105,10 +105,13 @@ fn extract_verifier(config: ExtractVerifierConfig) -> eyre::Result<ExitCode> {
         VerificationKey::<Bn254>::from_reader(File::open(vk).context("while opening input file")?)
             .context("while parsing verification-key")?;
 
-    let contract = SolidityVerifierContext {
-        vk: vk.into(),
-        config: SolidityVerifierConfig { pragma_version },
-    };
+    let contract = SolidityVerifierContext::new(
+        vk.into(),
+        SolidityVerifierConfig {
+            pragma_version,
+            ..Default::default()
+        },
+    );
     let rendered = contract.render().unwrap();
     if let Some(output) = output {
         std::fs::write(output, rendered).context("while writing output")?;
@@ -19,10 +19,7 @@
 //! use taceo_groth16_sol::askama::Template;
 //! let config = SolidityVerifierConfig::default();
 //! let vk : ark_groth16::VerifyingKey<ark_bn254::Bn254> = load_verification_key();
-//! let contract = SolidityVerifierContext {
-//!     vk,
-//!     config,
-//! };
+//! let contract = SolidityVerifierContext::new(vk, config);
 //! let rendered = contract.render().unwrap();
 //! println!("{}", rendered);
 //! // You can also write the rendered contract to a file, see askama documentation for details
@@ -40,19 +37,28 @@
 //! let compressed_proof = taceo_groth16_sol::prepare_compressed_proof(&proof);
 //! let uncompressed_proof = taceo_groth16_sol::prepare_uncompressed_proof(&proof);
 //! ```
+//! ## Commitments
+//! With the `commitment` feature, the generated contract supports gnark-style Pedersen commitments (e.g., from `api.Commit`)
+//! if the `CommitmentVerifyingKey` of `taceo-groth16` is set with `SolidityVerifierContext::with_commitment`. Like gnark, the
+//! template supports at most one commitment. The commitment wire is derived with the hash function
+//! configured in the [`SolidityVerifierConfig`], which must match the hash function used by the prover
+//! (see [`keccak256_hash_to_field`]). The commitments and the proof of knowledge are passed to the
+//! contract separately from the proof, see `prepare_uncompressed_commitments` and `prepare_compressed_commitments`.
 #![deny(missing_docs)]
 
 use alloy_primitives::U256;
 use ark_bn254::{Fq, G1Affine, G2Affine};
 use ark_ec::AffineRepr;
-use ark_ff::Field;
+use ark_ff::{Field, PrimeField};
 use ark_groth16::Proof;
+#[cfg(feature = "commitment")]
+use groth16::ProofWithCommitments;
 
 /// Re-export askama for users of this crate
 #[cfg(feature = "template")]
 pub use askama;
 #[cfg(feature = "template")]
-pub use template::{SolidityVerifierConfig, SolidityVerifierContext};
+pub use template::{HashToFieldFunction, SolidityVerifierConfig, SolidityVerifierContext};
 
 #[cfg(feature = "template")]
 mod template {
@@ -60,40 +66,141 @@ mod template {
     use ark_groth16::VerifyingKey;
     use askama::Template;
 
+    /// The verifying key of the Pedersen commitments, as used by the template.
+    #[derive(Debug, Clone)]
+    struct PedersenVerifyingKey {
+        g: ark_bn254::G2Affine,
+        g_sigma_neg: ark_bn254::G2Affine,
+        public_committed: Vec<Vec<usize>>,
+    }
+
     /// Context for generating a Solidity verifier contract for BN254 Groth16 proofs.
     /// The context is passed to `askama` for template rendering.
     /// Parameters:
     /// - `vk`: The [verifying key](ark_groth16::VerifyingKey) for the BN254 curve.
     /// - `config`: Configuration options for the Solidity verifier contract generation.
+    ///
+    /// Create it with [`SolidityVerifierContext::new`]. With the `commitment` feature, the verifying key of gnark-style
+    /// Pedersen commitments is set with `SolidityVerifierContext::with_commitment`.
     #[derive(Debug, Clone, Template)]
     #[template(path = "../templates/bn254_verifier.sol", escape = "none")]
+    #[non_exhaustive]
     pub struct SolidityVerifierContext {
         /// The Groth16 verifying key
         pub vk: VerifyingKey<ark_bn254::Bn254>,
+        /// The verifying key of the Pedersen commitments, if any. At most one commitment is supported.
+        commitment: Option<PedersenVerifyingKey>,
         /// Configuration options for the Solidity verifier contract generation
         pub config: SolidityVerifierConfig,
     }
 
+    impl SolidityVerifierContext {
+        /// Creates a context for a verifier without commitments.
+        pub fn new(vk: VerifyingKey<ark_bn254::Bn254>, config: SolidityVerifierConfig) -> Self {
+            Self {
+                vk,
+                commitment: None,
+                config,
+            }
+        }
+
+        /// Sets the verifying key of the gnark-style Pedersen commitments of the circuit. At most one commitment is
+        /// supported, rendering fails otherwise.
+        #[cfg(feature = "commitment")]
+        pub fn with_commitment(
+            mut self,
+            commitment: groth16::CommitmentVerifyingKey<ark_bn254::Bn254>,
+        ) -> Self {
+            self.commitment = Some(PedersenVerifyingKey {
+                g: commitment.g,
+                g_sigma_neg: commitment.g_sigma_neg,
+                public_committed: commitment.public_committed,
+            });
+            self
+        }
+
+        /// Returns the number of commitments, failing the rendering if the template does not support it.
+        ///
+        /// The committed public inputs must be public inputs of the proof, not commitment wires, otherwise the contract would
+        /// read them out of bounds.
+        fn num_commitments(&self) -> askama::Result<usize> {
+            let Some(commitment) = &self.commitment else {
+                return Ok(0);
+            };
+            let num_commitments = commitment.public_committed.len();
+            if num_commitments > 1 {
+                return Err(askama::Error::custom(format!(
+                    "at most one commitment is supported, got {num_commitments}"
+                )));
+            }
+            let num_public = self
+                .vk
+                .gamma_abc_g1
+                .len()
+                .checked_sub(1 + num_commitments)
+                .ok_or_else(|| {
+                    askama::Error::custom("verifying key has fewer public inputs than commitments")
+                })?;
+            if let Some(idx) = commitment
+                .public_committed
+                .iter()
+                .flatten()
+                .find(|idx| **idx >= num_public)
+            {
+                return Err(askama::Error::custom(format!(
+                    "committed public input {idx} out of range for {num_public} public inputs"
+                )));
+            }
+            Ok(num_commitments)
+        }
+    }
+
+    /// The hash function used to derive the commitment wire from a Pedersen commitment.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub enum HashToFieldFunction {
+        /// Keccak-256, the default of gnark for proofs verified in Solidity.
+        #[default]
+        Keccak256,
+        /// SHA-256, cheaper in some environments.
+        Sha256,
+    }
+
+    impl HashToFieldFunction {
+        /// Returns the name of the Solidity builtin.
+        fn solidity_name(&self) -> &'static str {
+            match self {
+                HashToFieldFunction::Keccak256 => "keccak256",
+                HashToFieldFunction::Sha256 => "sha256",
+            }
+        }
+    }
+
     /// Configuration for the Solidity verifier contract generation.
     ///
     /// Parameters:
     /// - `pragma_version`: The Solidity pragma version to use in the generated contract. Default is "^0.8.0".
+    /// - `hash_to_field`: The hash function used for Pedersen commitments. Default is Keccak-256.
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
     pub struct SolidityVerifierConfig {
         /// The Solidity pragma version to use in the generated contract. Default is "^0.8.0".
         pub pragma_version: String,
+        /// The hash function used to derive the commitment wires. Default is Keccak-256. Unused without commitments.
+        pub hash_to_field: HashToFieldFunction,
     }
 
     impl Default for SolidityVerifierConfig {
         fn default() -> Self {
             Self {
                 pragma_version: "^0.8.0".to_string(),
+                hash_to_field: HashToFieldFunction::default(),
             }
         }
     }
 
     #[cfg(test)]
     mod tests {
+        #[cfg(feature = "commitment")]
+        use ark_ec::AffineRepr;
         use askama::Template;
         use circom_types::groth16::VerificationKey;
 
@@ -105,16 +212,56 @@ mod template {
             let config = super::SolidityVerifierConfig::default();
             let vk =
                 serde_json::from_str::<VerificationKey<ark_bn254::Bn254>>(TEST_VK_BN254).unwrap();
-            let contract = super::SolidityVerifierContext {
-                vk: vk.into(),
-                config,
-            };
+            let contract = super::SolidityVerifierContext::new(vk.into(), config);
 
             let rendered = contract.render().unwrap();
             // Askama supresses trailing newlines, so we add one for comparison
             let rendered = format!("{}\n", rendered);
             assert_eq!(rendered, TEST_GNARK_OUTPUT);
         }
+
+        #[cfg(feature = "commitment")]
+        #[test]
+        fn test_commitment() {
+            let vk =
+                serde_json::from_str::<VerificationKey<ark_bn254::Bn254>>(TEST_VK_BN254).unwrap();
+            let config = super::SolidityVerifierConfig {
+                hash_to_field: super::HashToFieldFunction::Sha256,
+                ..Default::default()
+            };
+            let mut contract = super::SolidityVerifierContext::new(vk.into(), config)
+                .with_commitment(groth16::CommitmentVerifyingKey {
+                    g: ark_bn254::G2Affine::generator(),
+                    g_sigma_neg: -ark_bn254::G2Affine::generator(),
+                    public_committed: vec![vec![0]],
+                });
+            let num_public = contract.vk.gamma_abc_g1.len() - 1;
+
+            let rendered = contract.render().unwrap();
+            assert!(rendered.contains("error CommitmentInvalid();"));
+            assert!(rendered.contains("uint256[2] calldata commitmentPok,"));
+            assert!(rendered.contains(&format!("uint256[{}] calldata input", num_public - 1)));
+            assert!(rendered.contains("s :=  mload(publicCommitments)"));
+            assert!(rendered.contains("sha256(\n"));
+
+            // the last public input is the commitment wire
+            contract.commitment.as_mut().unwrap().public_committed = vec![vec![num_public - 1]];
+            assert!(
+                contract
+                    .render()
+                    .unwrap_err()
+                    .to_string()
+                    .contains("committed public input")
+            );
+
+            contract
+                .commitment
+                .as_mut()
+                .unwrap()
+                .public_committed
+                .push(vec![]);
+            assert!(contract.render().is_err());
+        }
     }
 }
 
@@ -224,6 +371,51 @@ pub fn prepare_uncompressed_proof(proof: &Proof<ark_bn254::Bn254>) -> [U256; 8]
     ]
 }
 
+/// Hashes `message` with Keccak-256 and reduces the big-endian digest modulo the field order.
+///
+/// This is the hash function used by the generated contract with [`HashToFieldFunction::Keccak256`](crate::HashToFieldFunction)
+/// to derive the commitment wires. Pass it to the prover, e.g., `Groth16::prove_with_commitments` of `taceo-groth16`. For
+/// SHA-256, use `sha256_hash_to_field` of `taceo-groth16`.
+pub fn keccak256_hash_to_field(message: &[u8]) -> ark_bn254::Fr {
+    ark_bn254::Fr::from_be_bytes_mod_order(alloy_primitives::keccak256(message).as_slice())
+}
+
+/// Prepare the Pedersen commitments and the proof of knowledge of a proof for verification in the generated contract.
+///
+/// The commitments are represented as the x and y coordinates of every commitment, the proof of
+/// knowledge as its x and y coordinates. The proof itself is prepared with [`prepare_uncompressed_proof`].
+#[cfg(feature = "commitment")]
+pub fn prepare_uncompressed_commitments(
+    proof: &ProofWithCommitments<ark_bn254::Bn254>,
+) -> (Vec<U256>, [U256; 2]) {
+    let commitments = proof
+        .commitments
+        .iter()
+        .flat_map(|commitment| {
+            // Infinity is represented as (0, 0)
+            let (x, y) = commitment.xy().unwrap_or_default();
+            [x.into(), y.into()]
+        })
+        .collect();
+    let (x, y) = proof.commitment_pok.xy().unwrap_or_default();
+    (commitments, [x.into(), y.into()])
+}
+
+/// Compress the Pedersen commitments and the proof of knowledge of a proof for verification in the generated contract.
+///
+/// The proof itself is prepared with [`prepare_compressed_proof`].
+///
+/// # Panics
+///
+/// This function will panic if the commitments contain points that are not on the curve.
+#[cfg(feature = "commitment")]
+pub fn prepare_compressed_commitments(
+    proof: &ProofWithCommitments<ark_bn254::Bn254>,
+) -> (Vec<U256>, U256) {
+    let commitments = proof.commitments.iter().map(compress_g1_point).collect();
+    (commitments, compress_g1_point(&proof.commitment_pok))
+}
+
 /// An error type representing an invalid compressed point during decompression.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InvalidCompressedPoint;
@@ -1,5 +1,5 @@
 {%- let numPublic = vk.gamma_abc_g1.len() - 1 -%}
-{%- let numCommitments = 0 -%}
+{%- let numCommitments = self.num_commitments()? -%}
 {%- let numWitness = numPublic - numCommitments %}
 // SPDX-License-Identifier: MIT
 
@@ -23,6 +23,14 @@ contract Verifier {
     /// curves, that pairing equation fails, or that the proof is not for the
     /// provided public input.
     error ProofInvalid();
+    {%- if numCommitments > 0 %}
+
+    /// The commitment is invalid.
+    /// @dev This can mean that provided commitment points and/or proof of knowledge are not on their
+    /// curves, that the pairing equation fails, or that the commitment and/or proof of knowledge is not
+    /// for the commitment key.
+    error CommitmentInvalid();
+    {%- endif %}
 
     // Addresses of precompiles
     uint256 constant PRECOMPILE_MODEXP = 0x05;
@@ -78,6 +86,20 @@ contract Verifier {
     uint256 constant DELTA_NEG_X_1 = {{ delta_neg.x().unwrap().c1 }};
     uint256 constant DELTA_NEG_Y_0 = {{ delta_neg.y().unwrap().c0 }};
     uint256 constant DELTA_NEG_Y_1 = {{ delta_neg.y().unwrap().c1 }};
+    {%- if let Some(commitment) = commitment %}
+
+    // Pedersen G point in G2 in powers of i
+    uint256 constant PEDERSEN_G_X_0 = {{ commitment.g.x().unwrap().c0 }};
+    uint256 constant PEDERSEN_G_X_1 = {{ commitment.g.x().unwrap().c1 }};
+    uint256 constant PEDERSEN_G_Y_0 = {{ commitment.g.y().unwrap().c0 }};
+    uint256 constant PEDERSEN_G_Y_1 = {{ commitment.g.y().unwrap().c1 }};
+
+    // Pedersen GSigmaNeg point in G2 in powers of i
+    uint256 constant PEDERSEN_GSIGMANEG_X_0 = {{ commitment.g_sigma_neg.x().unwrap().c0 }};
+    uint256 constant PEDERSEN_GSIGMANEG_X_1 = {{ commitment.g_sigma_neg.x().unwrap().c1 }};
+    uint256 constant PEDERSEN_GSIGMANEG_Y_0 = {{ commitment.g_sigma_neg.y().unwrap().c0 }};
+    uint256 constant PEDERSEN_GSIGMANEG_Y_1 = {{ commitment.g_sigma_neg.y().unwrap().c1 }};
+    {%- endif %}
 
     // Constant and public input points
     {% let k0 = vk.gamma_abc_g1[0] -%}
@@ -363,9 +385,21 @@ contract Verifier {
     /// @notice Computes the multi-scalar-multiplication of the public input
     /// elements and the verification key including the constant term.
     /// @param input The public inputs. These are elements of the scalar field Fr.
+    {%- if numCommitments > 0 %}
+    /// @param publicCommitments The public inputs derived from the Pedersen commitments.
+    /// @param commitments The Pedersen commitments from the proof.
+    {%- endif %}
     /// @return x The X coordinate of the resulting G1 point.
     /// @return y The Y coordinate of the resulting G1 point.
+    {%- if numCommitments > 0 %}
+    function publicInputMSM(
+        uint256[{{ numWitness }}] calldata input,
+        uint256[{{ numCommitments }}] memory publicCommitments,
+        uint256[{{ 2 * numCommitments }}] memory commitments
+    )
+    {%- else %}
     function publicInputMSM(uint256[{{ numWitness }}] calldata input)
+    {%- endif %}
     internal view returns (uint256 x, uint256 y) {
         // Note: The ECMUL precompile does not reject unreduced values, so we check this.
         // Note: Unrolling this loop does not cost much extra in code-size, the bulk of the
@@ -382,13 +416,22 @@ contract Verifier {
             let s
             mstore(f, CONSTANT_X)
             mstore(add(f, 0x20), CONSTANT_Y)
+            {%- for i in 0..numCommitments %}
+            mstore(g, mload(add(commitments, {{ i * 0x40 }})))
+            mstore(add(g, 0x20), mload(add(commitments, {{ i * 0x40 + 0x20 }})))
+            success := and(success, staticcall(gas(), PRECOMPILE_ADD, f, 0x80, f, 0x40))
+            {%- endfor %}
             {%- for i in (0..numPublic) %}
             mstore(g, PUB_{{ loop.index0 }}_X)
             mstore(add(g, 0x20), PUB_{{ loop.index0 }}_Y)
-            {% if loop.index0 == 0 -%}
+            {% if loop.index0 == 0 && numWitness > 0 -%}
             s :=  calldataload(input)
             {% elif loop.index0 < numWitness -%}
             s :=  calldataload(add(input, {{ loop.index0 * 0x20}}))
+            {% elif loop.index0 == numWitness -%}
+            s :=  mload(publicCommitments)
+            {% else -%}
+            s :=  mload(add(publicCommitments, {{ (loop.index0 - numWitness) * 0x20 }}))
             {% endif -%}
             mstore(add(g, 0x40), s)
             success := and(success, lt(s, R))
@@ -411,13 +454,38 @@ contract Verifier {
     /// but does not verify the proof itself.
     /// @param proof The uncompressed Groth16 proof. Elements are in the same order as for
     /// verifyProof. I.e. Groth16 points (A, B, C) encoded as in EIP-197.
+    {%- if numCommitments > 0 %}
+    /// @param commitments The Pedersen commitments from the proof.
+    /// @param commitmentPok The proof of knowledge for the Pedersen commitments.
+    {%- endif %}
     /// @return compressed The compressed proof. Elements are in the same order as for
     /// verifyCompressedProof. I.e. points (A, B, C) in compressed format.
+    {%- if numCommitments > 0 %}
+    /// @return compressedCommitments The compressed Pedersen commitments from the proof.
+    /// @return compressedCommitmentPok The compressed proof of knowledge for the Pedersen commitments.
+    function compressProof(
+        uint256[8] calldata proof,
+        uint256[{{ 2 * numCommitments }}] calldata commitments,
+        uint256[2] calldata commitmentPok
+    )
+    public view returns (
+        uint256[4] memory compressed,
+        uint256[{{ numCommitments }}] memory compressedCommitments,
+        uint256 compressedCommitmentPok
+    ) {
+    {%- else %}
     function compressProof(uint256[8] calldata proof)
     public view returns (uint256[4] memory compressed) {
+    {%- endif %}
         compressed[0] = compress_g1(proof[0], proof[1]);
         (compressed[2], compressed[1]) = compress_g2(proof[3], proof[2], proof[5], proof[4]);
         compressed[3] = compress_g1(proof[6], proof[7]);
+        {%- for i in 0..numCommitments %}
+        compressedCommitments[{{ i }}] = compress_g1(commitments[{{ 2 * i }}], commitments[{{ 2 * i + 1 }}]);
+        {%- endfor %}
+        {%- if numCommitments > 0 %}
+        compressedCommitmentPok = compress_g1(commitmentPok[0], commitmentPok[1]);
+        {%- endif %}
     }
 
     /// Verify a Groth16 proof with compressed points.
@@ -427,19 +495,84 @@ contract Verifier {
     /// proof was successfully verified.
     /// @param compressedProof the points (A, B, C) in compressed format
     /// matching the output of compressProof.
+    {%- if numCommitments > 0 %}
+    /// @param compressedCommitments the compressed Pedersen commitments from the proof.
+    /// @param compressedCommitmentPok the compressed proof of knowledge for the Pedersen commitments.
+    {%- endif %}
     /// @param input the public input field elements in the scalar field Fr.
     /// Elements must be reduced.
     function verifyCompressedProof(
         uint256[4] calldata compressedProof,
+        {%- if numCommitments > 0 %}
+        uint256[{{ numCommitments }}] calldata compressedCommitments,
+        uint256 compressedCommitmentPok,
+        {%- endif %}
         uint256[{{ numWitness }}] calldata input
     ) public view {
+        {%- if let Some(commitment) = commitment %}
+        uint256[{{ 2 * numCommitments }}] memory commitments;
+        {%- for i in 0..numCommitments %}
+        (commitments[{{ 2 * i }}], commitments[{{ 2 * i + 1 }}]) = decompress_g1(compressedCommitments[{{ i }}]);
+        {%- endfor %}
+        (uint256 PoKx, uint256 PoKy) = decompress_g1(compressedCommitmentPok);
+
+        // Derive the public inputs of the commitments
+        uint256[{{ numCommitments }}] memory publicCommitments;
+        {%- for committed in commitment.public_committed %}
+        {%- let i = loop.index0 %}
+        publicCommitments[{{ i }}] = uint256(
+            {{ config.hash_to_field.solidity_name() }}(
+                abi.encodePacked(
+                    commitments[{{ 2 * i }}],
+                    commitments[{{ 2 * i + 1 }}]
+                    {%- for j in committed %}
+                    , input[{{ j }}]
+                    {%- endfor %}
+                )
+            )
+        ) % R;
+        {%- endfor %}
+
+        // Verify the proof of knowledge of the Pedersen commitment
+        {
+            uint256[12] memory commitmentPairings;
+            // e(D, -σ⋅G)
+            commitmentPairings[ 0] = commitments[0];
+            commitmentPairings[ 1] = commitments[1];
+            commitmentPairings[ 2] = PEDERSEN_GSIGMANEG_X_1;
+            commitmentPairings[ 3] = PEDERSEN_GSIGMANEG_X_0;
+            commitmentPairings[ 4] = PEDERSEN_GSIGMANEG_Y_1;
+            commitmentPairings[ 5] = PEDERSEN_GSIGMANEG_Y_0;
+            // e(PoK, G)
+            commitmentPairings[ 6] = PoKx;
+            commitmentPairings[ 7] = PoKy;
+            commitmentPairings[ 8] = PEDERSEN_G_X_1;
+            commitmentPairings[ 9] = PEDERSEN_G_X_0;
+            commitmentPairings[10] = PEDERSEN_G_Y_1;
+            commitmentPairings[11] = PEDERSEN_G_Y_0;
+
+            bool commitmentValid;
+            uint256[1] memory commitmentOutput;
+            assembly ("memory-safe") {
+                commitmentValid := staticcall(gas(), PRECOMPILE_VERIFY, commitmentPairings, 0x180, commitmentOutput, 0x20)
+            }
+            if (!commitmentValid || commitmentOutput[0] != 1) {
+                // Either the commitment or the proof of knowledge is invalid.
+                revert CommitmentInvalid();
+            }
+        }
+{% endif %}
         uint256[24] memory pairings;
 
         {
             (uint256 Ax, uint256 Ay) = decompress_g1(compressedProof[0]);
             (uint256 Bx0, uint256 Bx1, uint256 By0, uint256 By1) = decompress_g2(compressedProof[2], compressedProof[1]);
             (uint256 Cx, uint256 Cy) = decompress_g1(compressedProof[3]);
+            {%- if numCommitments > 0 %}
+            (uint256 Lx, uint256 Ly) = publicInputMSM(input, publicCommitments, commitments);
+            {%- else %}
             (uint256 Lx, uint256 Ly) = publicInputMSM(input);
+            {%- endif %}
 
             // Verify the pairing
             // Note: The precompile expects the F2 coefficients in big-endian order.
@@ -494,13 +627,71 @@ contract Verifier {
     /// proof was successfully verified.
     /// @param proof the points (A, B, C) in EIP-197 format matching the output
     /// of compressProof.
+    {%- if numCommitments > 0 %}
+    /// @param commitments the Pedersen commitments from the proof.
+    /// @param commitmentPok the proof of knowledge for the Pedersen commitments.
+    {%- endif %}
     /// @param input the public input field elements in the scalar field Fr.
     /// Elements must be reduced.
     function verifyProof(
         uint256[8] calldata proof,
+        {%- if numCommitments > 0 %}
+        uint256[{{ 2 * numCommitments }}] calldata commitments,
+        uint256[2] calldata commitmentPok,
+        {%- endif %}
         uint256[{{ numWitness }}] calldata input
     ) public view {
+        {%- if let Some(commitment) = commitment %}
+        // Derive the public inputs of the commitments
+        uint256[{{ numCommitments }}] memory publicCommitments;
+        {%- for committed in commitment.public_committed %}
+        {%- let i = loop.index0 %}
+        publicCommitments[{{ i }}] = uint256(
+            {{ config.hash_to_field.solidity_name() }}(
+                abi.encodePacked(
+                    commitments[{{ 2 * i }}],
+                    commitments[{{ 2 * i + 1 }}]
+                    {%- for j in committed %}
+                    , input[{{ j }}]
+                    {%- endfor %}
+                )
+            )
+        ) % R;
+        {%- endfor %}
+
+        // Verify the proof of knowledge of the Pedersen commitment
+        {
+            uint256[12] memory commitmentPairings;
+            // e(D, -σ⋅G)
+            commitmentPairings[ 0] = commitments[0];
+            commitmentPairings[ 1] = commitments[1];
+            commitmentPairings[ 2] = PEDERSEN_GSIGMANEG_X_1;
+            commitmentPairings[ 3] = PEDERSEN_GSIGMANEG_X_0;
+            commitmentPairings[ 4] = PEDERSEN_GSIGMANEG_Y_1;
+            commitmentPairings[ 5] = PEDERSEN_GSIGMANEG_Y_0;
+            // e(PoK, G)
+            commitmentPairings[ 6] = commitmentPok[0];
+            commitmentPairings[ 7] = commitmentPok[1];
+            commitmentPairings[ 8] = PEDERSEN_G_X_1;
+            commitmentPairings[ 9] = PEDERSEN_G_X_0;
+            commitmentPairings[10] = PEDERSEN_G_Y_1;
+            commitmentPairings[11] = PEDERSEN_G_Y_0;
+
+            bool commitmentValid;
+            uint256[1] memory commitmentOutput;
+            assembly ("memory-safe") {
+                commitmentValid := staticcall(gas(), PRECOMPILE_VERIFY, commitmentPairings, 0x180, commitmentOutput, 0x20)
+            }
+            if (!commitmentValid || commitmentOutput[0] != 1) {
+                // Either the commitment or the proof of knowledge is invalid.
+                revert CommitmentInvalid();
+            }
+        }
+
+        (uint256 x, uint256 y) = publicInputMSM(input, publicCommitments, commitments);
+        {%- else %}
         (uint256 x, uint256 y) = publicInputMSM(input);
+        {%- endif %}
 
         // Note: The precompile expects the F2 coefficients in big-endian order.
         // Note: The pairing precompile rejects unreduced values, so we won't check that here.
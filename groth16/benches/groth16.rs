@@ -11,7 +11,7 @@ use ark_relations::{
 };
 use ark_snark::SNARK;
 use ark_std::rand::SeedableRng;
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
 use rayon::iter::{
     IndexedParallelIterator as _, IntoParallelIterator as _, IntoParallelRefIterator,
     IntoParallelRefMutIterator, ParallelIterator as _,
@@ -19,6 +19,9 @@ use rayon::iter::{
 
 const NUM_CONSTRAINTS: usize = (1 << 16) - 100;
 const NUM_VARIABLES: usize = (1 << 16) - 100;
+const BATCH_NUM_CONSTRAINTS: usize = (1 << 12) - 100;
+const BATCH_NUM_VARIABLES: usize = (1 << 12) - 100;
+const BATCH_SIZE: usize = 16;
 
 /// This implementation is copied from [qap.rs](https://github.com/arkworks-rs/circom-compat/blob/master/src/circom/qap.rs) of `ark-circom@v0.5.0`.
 /// As of Rust `v1.93`, `ark-circom` does not compile due to alignment issues.
@@ -267,6 +270,22 @@ fn groth16_prove_bench<P: Pairing>(
             .unwrap();
         })
     });
+    let circom_ctx =
+        taceo_groth16::ProverContext::<P, taceo_groth16::CircomReduction>::new(&pk, &matrices)
+            .unwrap();
+    group.bench_function("this-groth16/CircomReduction/context", |b| {
+        b.iter(|| {
+            let _ = taceo_groth16::Groth16::<P>::prove_with_context(
+                &circom_ctx,
+                &pk,
+                r,
+                s,
+                &matrices,
+                &full_assignment,
+            )
+            .unwrap();
+        })
+    });
     group.bench_function("this-groth16/LibSnarkReduction", |b| {
         b.iter(|| {
             let _ = taceo_groth16::Groth16::<P>::prove::<taceo_groth16::LibSnarkReduction>(
@@ -279,10 +298,91 @@ fn groth16_prove_bench<P: Pairing>(
             .unwrap();
         })
     });
+    let libsnark_ctx =
+        taceo_groth16::ProverContext::<P, taceo_groth16::LibSnarkReduction>::new(&pk, &matrices)
+            .unwrap();
+    group.bench_function("this-groth16/LibSnarkReduction/context", |b| {
+        b.iter(|| {
+            let _ = taceo_groth16::Groth16::<P>::prove_with_context(
+                &libsnark_ctx,
+                &pk,
+                r,
+                s,
+                &matrices,
+                &full_assignment,
+            )
+            .unwrap();
+        })
+    });
+}
+
+fn groth16_prove_batch_bench<P: Pairing>(
+    bench_name: &str,
+    c: &mut Criterion,
+    num_constraints: usize,
+    num_variables: usize,
+    batch_size: usize,
+) {
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+    let circuit = DummyCircuit::<P::ScalarField> {
+        a: Some(P::ScalarField::rand(rng)),
+        b: Some(P::ScalarField::rand(rng)),
+        num_variables,
+        num_constraints,
+    };
+
+    let (pk, _) = Groth16::<P>::circuit_specific_setup(circuit, rng).unwrap();
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap();
+    let prover = cs.borrow().unwrap();
+    let full_assignment = [
+        prover.instance_assignment.as_slice(),
+        prover.witness_assignment.as_slice(),
+    ]
+    .concat();
+    let witnesses = vec![full_assignment; batch_size];
+    let ctx =
+        taceo_groth16::ProverContext::<P, taceo_groth16::CircomReduction>::new(&pk, &matrices)
+            .unwrap();
+
+    let mut group = c.benchmark_group(format!(
+        "{bench_name} - {num_constraints} constraints - {num_variables} variables - batch of {batch_size}"
+    ));
+    group.throughput(Throughput::Elements(batch_size as u64));
+    group.bench_function("this-groth16/CircomReduction/loop", |b| {
+        b.iter(|| {
+            for witness in witnesses.iter() {
+                let (r, s) = (P::ScalarField::rand(rng), P::ScalarField::rand(rng));
+                let _ = taceo_groth16::Groth16::<P>::prove_with_context(
+                    &ctx, &pk, r, s, &matrices, witness,
+                )
+                .unwrap();
+            }
+        })
+    });
+    group.bench_function("this-groth16/CircomReduction/batch", |b| {
+        b.iter(|| {
+            let proofs = taceo_groth16::Groth16::<P>::prove_batch_with_context(
+                &ctx, &pk, &matrices, &witnesses, rng,
+            );
+            assert!(proofs.iter().all(Result::is_ok));
+        })
+    });
 }
 
 fn groth16_bench(c: &mut Criterion) {
     groth16_prove_bench::<ark_bn254::Bn254>("bn254", c, NUM_CONSTRAINTS, NUM_VARIABLES);
+    groth16_prove_batch_bench::<ark_bn254::Bn254>(
+        "bn254",
+        c,
+        BATCH_NUM_CONSTRAINTS,
+        BATCH_NUM_VARIABLES,
+        BATCH_SIZE,
+    );
 }
 
 criterion_group!(benches, groth16_bench);
@@ -0,0 +1,641 @@
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use tracing::instrument;
+
+use crate::{Groth16Error, Proof, VerifyingKey, cfg_iter, rayon_join5};
+
+pub use srs::{GenericSrs, ProverSrs, VerifierSrs};
+use transcript::Transcript;
+
+mod srs;
+mod transcript;
+
+/// Domain separator of the Fiat-Shamir transcript.
+const DST: &[u8] = b"TACEO-GROTH16-SNARKPACK-V1";
+
+/// A pair of commitments under the keys derived from `alpha` and `beta`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct Commitment<P: Pairing> {
+    t: PairingOutput<P>,
+    u: PairingOutput<P>,
+}
+
+impl<P: Pairing> Commitment<P> {
+    /// Commits to `a` with the G2 keys `v` and to `b` with the G1 keys `w`.
+    fn pair(
+        (v1, v2): (&[P::G2Affine], &[P::G2Affine]),
+        (w1, w2): (&[P::G1Affine], &[P::G1Affine]),
+        a: &[P::G1Affine],
+        b: &[P::G2Affine],
+    ) -> Self {
+        let (t, u) = crate::join(
+            || P::multi_pairing(a.iter().chain(w1), v1.iter().chain(b)),
+            || P::multi_pairing(a.iter().chain(w2), v2.iter().chain(b)),
+        );
+        Self { t, u }
+    }
+
+    /// Commits to `c` with the G2 keys `v`.
+    fn single((v1, v2): (&[P::G2Affine], &[P::G2Affine]), c: &[P::G1Affine]) -> Self {
+        let (t, u) = crate::join(|| P::multi_pairing(c, v1), || P::multi_pairing(c, v2));
+        Self { t, u }
+    }
+
+    /// Computes `left * x + self + right * x_inv`.
+    fn fold(&self, left: &Self, right: &Self, x: P::ScalarField, x_inv: P::ScalarField) -> Self {
+        Self {
+            t: left.t * x + self.t + right.t * x_inv,
+            u: left.u * x + self.u + right.u * x_inv,
+        }
+    }
+}
+
+/// The messages of one round of the inner product argument.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct GipaRound<P: Pairing> {
+    com_ab_l: Commitment<P>,
+    com_ab_r: Commitment<P>,
+    z_ab_l: PairingOutput<P>,
+    z_ab_r: PairingOutput<P>,
+    com_c_l: Commitment<P>,
+    com_c_r: Commitment<P>,
+    z_c_l: P::G1Affine,
+    z_c_r: P::G1Affine,
+}
+
+/// An aggregate of many Groth16 proofs under the same verifying key, following [SnarkPack](https://eprint.iacr.org/2021/529).
+///
+/// The size of the aggregate proof and the work of the verifier (apart from the public inputs)
+/// are logarithmic in the number of proofs. Create it with [`aggregate_proofs`] and verify it
+/// with [`verify_aggregate_proof`].
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregateProof<P: Pairing> {
+    com_ab: Commitment<P>,
+    com_c: Commitment<P>,
+    ip_ab: PairingOutput<P>,
+    agg_c: P::G1Affine,
+    rounds: Vec<GipaRound<P>>,
+    final_a: P::G1Affine,
+    final_b: P::G2Affine,
+    final_c: P::G1Affine,
+    final_v: (P::G2Affine, P::G2Affine),
+    final_w: (P::G1Affine, P::G1Affine),
+    opening_v: (P::G2Affine, P::G2Affine),
+    opening_w: (P::G1Affine, P::G1Affine),
+}
+
+impl<P: Pairing> AggregateProof<P> {
+    /// Returns the number of aggregated proofs, including the padding to the next power of two.
+    ///
+    /// Returns [`Groth16Error::TooManyAggregationRounds`] if the number of rounds of a (deserialized) proof does not
+    /// fit into a `usize`.
+    pub fn num_proofs(&self) -> Result<usize, Groth16Error> {
+        u32::try_from(self.rounds.len())
+            .ok()
+            .and_then(|rounds| 1usize.checked_shl(rounds))
+            .ok_or(Groth16Error::TooManyAggregationRounds(self.rounds.len()))
+    }
+}
+
+/// Returns `[1, x, x^2, ..., x^{len-1}]`.
+fn powers<F: Field>(x: F, len: usize) -> Vec<F> {
+    std::iter::successors(Some(F::one()), |p| Some(*p * x))
+        .take(len)
+        .collect()
+}
+
+/// Multiplies every point with the corresponding scalar.
+fn scale<G: AffineRepr>(points: &[G], scalars: &[G::ScalarField]) -> Vec<G> {
+    let scaled = cfg_iter!(points)
+        .zip(cfg_iter!(scalars))
+        .map(|(p, s)| *p * s)
+        .collect::<Vec<_>>();
+    G::Group::normalize_batch(&scaled)
+}
+
+/// Folds the two halves of `points` into `left + right * x`.
+fn fold_points<G: AffineRepr>(points: &[G], x: G::ScalarField) -> Vec<G> {
+    let (left, right) = points.split_at(points.len() / 2);
+    let folded = cfg_iter!(left)
+        .zip(cfg_iter!(right))
+        .map(|(l, r)| *r * x + l)
+        .collect::<Vec<_>>();
+    G::Group::normalize_batch(&folded)
+}
+
+/// Folds the two halves of `scalars` into `left + right * x`.
+fn fold_scalars<F: Field>(scalars: &[F], x: F) -> Vec<F> {
+    let (left, right) = scalars.split_at(scalars.len() / 2);
+    left.iter().zip(right).map(|(l, r)| *l + *r * x).collect()
+}
+
+/// Computes the coefficients of `prod_j (1 + challenges[j] * X^{n / 2^{j+1}})` for `n = 2^challenges.len()`.
+///
+/// These are the factors with which the keys are folded by the inner product argument.
+fn folding_polynomial<F: Field>(challenges: &[F]) -> Vec<F> {
+    let mut coeffs = vec![F::one()];
+    for c in challenges.iter().rev() {
+        let shifted = coeffs.iter().map(|coeff| *coeff * c).collect::<Vec<_>>();
+        coeffs.extend(shifted);
+    }
+    coeffs
+}
+
+/// Evaluates `prod_j (1 + challenges[j] * (shift * z)^{n / 2^{j+1}})` in logarithmic time.
+fn evaluate_folding_polynomial<F: Field>(challenges: &[F], shift: F, z: F) -> F {
+    let n = 1u64 << challenges.len();
+    let x = shift * z;
+    challenges
+        .iter()
+        .enumerate()
+        .map(|(j, c)| F::one() + *c * x.pow([n >> (j + 1)]))
+        .product()
+}
+
+/// Computes the coefficients of the KZG quotient `(p(X) - p(z)) / (X - z)`.
+fn kzg_quotient<F: Field>(coeffs: &[F], z: F) -> Vec<F> {
+    let mut quotient = vec![F::zero(); coeffs.len().saturating_sub(1)];
+    let mut acc = F::zero();
+    for i in (1..coeffs.len()).rev() {
+        acc = coeffs[i] + acc * z;
+        quotient[i - 1] = acc;
+    }
+    quotient
+}
+
+/// Pads `values` to the next power of two by repeating the last element.
+fn pad<T: Clone>(values: &[T]) -> Vec<T> {
+    let mut padded = values.to_vec();
+    if let Some(last) = values.last() {
+        padded.resize(values.len().next_power_of_two(), last.clone());
+    }
+    padded
+}
+
+fn init_transcript<P: Pairing, I: AsRef<[P::ScalarField]>>(
+    n: usize,
+    public_inputs: &[I],
+) -> Transcript {
+    let mut transcript = Transcript::new(DST);
+    transcript.append(b"n", &(n as u64));
+    for inputs in public_inputs {
+        transcript.append(b"public inputs", inputs.as_ref());
+    }
+    // pad the public inputs like the proofs
+    for _ in public_inputs.len()..n {
+        let last = public_inputs.last().expect("at least one proof");
+        transcript.append(b"public inputs", last.as_ref());
+    }
+    transcript
+}
+
+/// Aggregates Groth16 proofs under the same verifying key into a single [`AggregateProof`].
+///
+/// `public_inputs` contains the public inputs of every proof. If the number of proofs is not a power of two, the proofs
+/// are padded by repeating the last proof. The [`ProverSrs`] must support at least the padded number of proofs.
+#[instrument(level = "debug", name = "aggregate proofs", skip_all)]
+pub fn aggregate_proofs<P: Pairing, I: AsRef<[P::ScalarField]>>(
+    srs: &ProverSrs<P>,
+    proofs: &[Proof<P>],
+    public_inputs: &[I],
+) -> Result<AggregateProof<P>, Groth16Error> {
+    if proofs.is_empty() {
+        return Err(Groth16Error::EmptyAggregation);
+    }
+    if proofs.len() != public_inputs.len() {
+        return Err(Groth16Error::ProofCountMismatch {
+            expected: proofs.len(),
+            actual: public_inputs.len(),
+        });
+    }
+    let n = proofs.len().next_power_of_two();
+    if n > srs.n {
+        return Err(Groth16Error::SrsTooSmall {
+            max: srs.n,
+            actual: proofs.len(),
+        });
+    }
+    let proofs = pad(proofs);
+    let a = proofs.iter().map(|p| p.a).collect::<Vec<_>>();
+    let b = proofs.iter().map(|p| p.b).collect::<Vec<_>>();
+    let c = proofs.iter().map(|p| p.c).collect::<Vec<_>>();
+
+    // v are the G2 keys, w are the G1 keys shifted by n
+    let mut v = (
+        srs.h_alpha_powers[..n].to_vec(),
+        srs.h_beta_powers[..n].to_vec(),
+    );
+    let w = (&srs.g_alpha_powers[n..2 * n], &srs.g_beta_powers[n..2 * n]);
+
+    let mut transcript = init_transcript::<P, _>(n, public_inputs);
+    let (com_ab, com_c) = crate::join(
+        || Commitment::pair((&v.0, &v.1), w, &a, &b),
+        || Commitment::single((&v.0, &v.1), &c),
+    );
+    transcript.append(b"com_ab", &com_ab);
+    transcript.append(b"com_c", &com_c);
+    let r = transcript.challenge::<P::ScalarField>(b"r");
+    let r_inv = r.inverse().expect("challenge is non-zero");
+
+    // the inner product argument runs on B' = B * r^i with the G1 keys w' = w * r^{-i}, so that the
+    // commitment to (A, B') under (v, w') equals the commitment to (A, B) under (v, w)
+    let mut r_vec = powers(r, n);
+    let r_inv_vec = powers(r_inv, n);
+    let mut b = scale(&b, &r_vec);
+    let mut w = (scale(w.0, &r_inv_vec), scale(w.1, &r_inv_vec));
+    let (ip_ab, agg_c) = crate::join(
+        || P::multi_pairing(&a, &b),
+        || P::G1::msm_unchecked(&c, &r_vec).into_affine(),
+    );
+    transcript.append(b"ip_ab", &ip_ab);
+    transcript.append(b"agg_c", &agg_c);
+
+    let mut a = a;
+    let mut c = c;
+    let mut challenges = Vec::with_capacity(n.ilog2() as usize);
+    let mut rounds = Vec::with_capacity(n.ilog2() as usize);
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_l, a_r) = a.split_at(half);
+        let (b_l, b_r) = b.split_at(half);
+        let (c_l, c_r) = c.split_at(half);
+        let (r_l, r_r) = r_vec.split_at(half);
+        let v_l = (&v.0[..half], &v.1[..half]);
+        let v_r = (&v.0[half..], &v.1[half..]);
+        let w_l = (&w.0[..half], &w.1[..half]);
+        let w_r = (&w.0[half..], &w.1[half..]);
+
+        let ((com_ab_l, com_ab_r), (z_ab_l, z_ab_r), (com_c_l, com_c_r), z_c_l, z_c_r) = rayon_join5!(
+            || crate::join(
+                || Commitment::pair(v_l, w_r, a_r, b_l),
+                || Commitment::pair(v_r, w_l, a_l, b_r),
+            ),
+            || crate::join(|| P::multi_pairing(a_r, b_l), || P::multi_pairing(a_l, b_r)),
+            || crate::join(
+                || Commitment::single(v_l, c_r),
+                || Commitment::single(v_r, c_l),
+            ),
+            || P::G1::msm_unchecked(c_r, r_l).into_affine(),
+            || P::G1::msm_unchecked(c_l, r_r).into_affine()
+        );
+        let round = GipaRound {
+            com_ab_l,
+            com_ab_r,
+            z_ab_l,
+            z_ab_r,
+            com_c_l,
+            com_c_r,
+            z_c_l,
+            z_c_r,
+        };
+        transcript.append(b"round", &round);
+        rounds.push(round);
+        let x = transcript.challenge::<P::ScalarField>(b"x");
+        let x_inv = x.inverse().expect("challenge is non-zero");
+
+        a = fold_points(&a, x);
+        b = fold_points(&b, x_inv);
+        c = fold_points(&c, x);
+        r_vec = fold_scalars(&r_vec, x_inv);
+        v = (fold_points(&v.0, x_inv), fold_points(&v.1, x_inv));
+        w = (fold_points(&w.0, x), fold_points(&w.1, x));
+        challenges.push(x);
+    }
+
+    let final_v = (v.0[0], v.1[0]);
+    let final_w = (w.0[0], w.1[0]);
+    transcript.append(b"final_a", &a[0]);
+    transcript.append(b"final_b", &b[0]);
+    transcript.append(b"final_c", &c[0]);
+    transcript.append(b"final_v", &final_v);
+    transcript.append(b"final_w", &final_w);
+    let z = transcript.challenge::<P::ScalarField>(b"z");
+
+    // prove that the final keys are the keys folded with the challenges
+    let challenges_inv = challenges
+        .iter()
+        .map(|x| x.inverse().expect("challenge is non-zero"))
+        .collect::<Vec<_>>();
+    let v_quotient = kzg_quotient(&folding_polynomial(&challenges_inv), z);
+    // the G1 keys are shifted by X^n and scaled by r^{-i}
+    let mut w_poly = vec![P::ScalarField::zero(); n];
+    w_poly.extend(
+        folding_polynomial(&challenges)
+            .into_iter()
+            .zip(r_inv_vec)
+            .map(|(coeff, r_inv)| coeff * r_inv),
+    );
+    let w_quotient = kzg_quotient(&w_poly, z);
+    let opening_v = (
+        P::G2::msm_unchecked(&srs.h_alpha_powers, &v_quotient).into_affine(),
+        P::G2::msm_unchecked(&srs.h_beta_powers, &v_quotient).into_affine(),
+    );
+    let opening_w = (
+        P::G1::msm_unchecked(&srs.g_alpha_powers, &w_quotient).into_affine(),
+        P::G1::msm_unchecked(&srs.g_beta_powers, &w_quotient).into_affine(),
+    );
+
+    Ok(AggregateProof {
+        com_ab,
+        com_c,
+        ip_ab,
+        agg_c,
+        rounds,
+        final_a: a[0],
+        final_b: b[0],
+        final_c: c[0],
+        final_v,
+        final_w,
+        opening_v,
+        opening_w,
+    })
+}
+
+/// Verifies an [`AggregateProof`] for the public inputs of every aggregated proof.
+///
+/// Returns [`Groth16Error::InvalidProof`] if the aggregate proof is invalid.
+#[instrument(level = "debug", name = "verify aggregate proof", skip_all)]
+pub fn verify_aggregate_proof<P: Pairing, I: AsRef<[P::ScalarField]>>(
+    srs: &VerifierSrs<P>,
+    vk: &VerifyingKey<P>,
+    proof: &AggregateProof<P>,
+    public_inputs: &[I],
+) -> Result<(), Groth16Error> {
+    if public_inputs.is_empty() {
+        return Err(Groth16Error::EmptyAggregation);
+    }
+    let num_public_inputs = vk
+        .gamma_abc_g1
+        .len()
+        .checked_sub(1)
+        .ok_or(Groth16Error::MalformedVerifyingKey)?;
+    for inputs in public_inputs {
+        if inputs.as_ref().len() != num_public_inputs {
+            return Err(Groth16Error::InvalidPublicInputLength {
+                expected: num_public_inputs,
+                actual: inputs.as_ref().len(),
+            });
+        }
+    }
+    let n = public_inputs.len().next_power_of_two();
+    let num_proofs = proof.num_proofs()?;
+    if num_proofs != n {
+        return Err(Groth16Error::ProofCountMismatch {
+            expected: num_proofs,
+            actual: public_inputs.len(),
+        });
+    }
+
+    let mut transcript = init_transcript::<P, _>(n, public_inputs);
+    transcript.append(b"com_ab", &proof.com_ab);
+    transcript.append(b"com_c", &proof.com_c);
+    let r = transcript.challenge::<P::ScalarField>(b"r");
+    let r_inv = r.inverse().expect("challenge is non-zero");
+
+    // the randomly combined Groth16 equations:
+    // prod e(A_i, B_i)^{r^i} = e(alpha, beta)^{sum r^i} * e(sum r^i S_i, gamma) * e(sum r^i C_i, delta)
+    let r_vec = powers(r, n);
+    let padded_inputs = pad(&public_inputs.iter().map(AsRef::as_ref).collect::<Vec<_>>());
+    let mut input_acc = vec![P::ScalarField::zero(); num_public_inputs];
+    for (inputs, r_i) in padded_inputs.iter().zip(&r_vec) {
+        for (acc, input) in input_acc.iter_mut().zip(inputs.iter()) {
+            *acc += *r_i * input;
+        }
+    }
+    let sum_r = r_vec.iter().sum::<P::ScalarField>();
+    let s = vk.gamma_abc_g1[0] * sum_r + P::G1::msm_unchecked(&vk.gamma_abc_g1[1..], &input_acc);
+    let rhs = P::pairing(vk.alpha_g1, vk.beta_g2) * sum_r
+        + P::multi_pairing([s.into_affine(), proof.agg_c], [vk.gamma_g2, vk.delta_g2]);
+    if proof.ip_ab != rhs {
+        return Err(Groth16Error::InvalidProof);
+    }
+    transcript.append(b"ip_ab", &proof.ip_ab);
+    transcript.append(b"agg_c", &proof.agg_c);
+
+    let mut com_ab = proof.com_ab.clone();
+    let mut com_c = proof.com_c.clone();
+    let mut z_ab = proof.ip_ab;
+    let mut z_c = proof.agg_c.into_group();
+    let mut challenges = Vec::with_capacity(proof.rounds.len());
+    for round in &proof.rounds {
+        transcript.append(b"round", round);
+        let x = transcript.challenge::<P::ScalarField>(b"x");
+        let x_inv = x.inverse().expect("challenge is non-zero");
+        com_ab = com_ab.fold(&round.com_ab_l, &round.com_ab_r, x, x_inv);
+        com_c = com_c.fold(&round.com_c_l, &round.com_c_r, x, x_inv);
+        z_ab = round.z_ab_l * x + z_ab + round.z_ab_r * x_inv;
+        z_c = round.z_c_l * x + z_c + round.z_c_r * x_inv;
+        challenges.push(x);
+    }
+    transcript.append(b"final_a", &proof.final_a);
+    transcript.append(b"final_b", &proof.final_b);
+    transcript.append(b"final_c", &proof.final_c);
+    transcript.append(b"final_v", &proof.final_v);
+    transcript.append(b"final_w", &proof.final_w);
+    let z = transcript.challenge::<P::ScalarField>(b"z");
+
+    let challenges_inv = challenges
+        .iter()
+        .map(|x| x.inverse().expect("challenge is non-zero"))
+        .collect::<Vec<_>>();
+    let one = P::ScalarField::one();
+    let final_r = evaluate_folding_polynomial(&challenges_inv, one, r);
+    let (v1, v2) = proof.final_v;
+    let (w1, w2) = proof.final_w;
+    let (a, b, c) = (proof.final_a, proof.final_b, proof.final_c);
+
+    // the final commitments and inner products
+    let valid_ab = com_ab.t == P::multi_pairing([a, w1], [v1, b])
+        && com_ab.u == P::multi_pairing([a, w2], [v2, b])
+        && z_ab == P::pairing(a, b);
+    let valid_c =
+        com_c.t == P::pairing(c, v1) && com_c.u == P::pairing(c, v2) && z_c == c * final_r;
+
+    // the KZG openings of the final keys at z
+    let v_eval = evaluate_folding_polynomial(&challenges_inv, one, z);
+    let w_eval = z.pow([n as u64]) * evaluate_folding_polynomial(&challenges, r_inv, z);
+    let g = srs.g.into_group();
+    let h = srs.h.into_group();
+    let g_z = g * z;
+    let h_z = h * z;
+    let kzg_v = |v: P::G2Affine, g_secret: P::G1Affine, opening: P::G2Affine| {
+        P::multi_pairing(
+            [srs.g, (g_z - g_secret).into_affine()],
+            [(v - h * v_eval).into_affine(), opening],
+        )
+        .is_zero()
+    };
+    let kzg_w = |w: P::G1Affine, h_secret: P::G2Affine, opening: P::G1Affine| {
+        P::multi_pairing(
+            [(w - g * w_eval).into_affine(), opening],
+            [srs.h, (h_z - h_secret).into_affine()],
+        )
+        .is_zero()
+    };
+    let valid_keys = kzg_v(v1, srs.g_alpha, proof.opening_v.0)
+        && kzg_v(v2, srs.g_beta, proof.opening_v.1)
+        && kzg_w(w1, srs.h_alpha, proof.opening_w.0)
+        && kzg_w(w2, srs.h_beta, proof.opening_w.1);
+
+    if valid_ab && valid_c && valid_keys {
+        Ok(())
+    } else {
+        Err(Groth16Error::InvalidProof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::UniformRand;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::test_utils::TestCircuit;
+    use crate::{Groth16, LibSnarkReduction};
+
+    /// Proves `num_proofs` different statements of the test circuit.
+    fn proofs(
+        circuit: &TestCircuit<Bn254>,
+        num_proofs: usize,
+        rng: &mut impl rand::RngCore,
+    ) -> (Vec<Proof<Bn254>>, Vec<Vec<Fr>>) {
+        (0..num_proofs)
+            .map(|_| {
+                let (a, b) = (Fr::rand(rng), Fr::rand(rng));
+                // the witness is [1, c, a, b, a^2, b^2]
+                let witness = [Fr::one(), a * b, a, b, a * a, b * b];
+                let proof = Groth16::prove::<LibSnarkReduction>(
+                    &circuit.pk,
+                    Fr::rand(rng),
+                    Fr::rand(rng),
+                    &circuit.matrices,
+                    &witness,
+                )
+                .unwrap();
+                (proof, vec![a * b])
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn aggregate_proof_verifies() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let srs = GenericSrs::<Bn254>::setup_insecure(&mut rng, 8);
+        let (prover_srs, verifier_srs) = srs.specialize(8).unwrap();
+        let vk = &circuit.pk.vk;
+
+        for num_proofs in [1, 2, 5, 8] {
+            let (proofs, inputs) = proofs(&circuit, num_proofs, &mut rng);
+            let aggregate = aggregate_proofs(&prover_srs, &proofs, &inputs).unwrap();
+            assert_eq!(
+                aggregate.num_proofs().unwrap(),
+                num_proofs.next_power_of_two()
+            );
+            verify_aggregate_proof(&verifier_srs, vk, &aggregate, &inputs).unwrap();
+
+            let mut bytes = Vec::new();
+            aggregate.serialize_compressed(&mut bytes).unwrap();
+            let deserialized = AggregateProof::deserialize_compressed(bytes.as_slice()).unwrap();
+            assert_eq!(aggregate, deserialized);
+        }
+    }
+
+    #[test]
+    fn aggregate_proof_rejects_wrong_statements() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let srs = GenericSrs::<Bn254>::setup_insecure(&mut rng, 4);
+        let (prover_srs, verifier_srs) = srs.specialize(4).unwrap();
+        let vk = &circuit.pk.vk;
+        let (mut proofs, mut inputs) = proofs(&circuit, 4, &mut rng);
+        let aggregate = aggregate_proofs(&prover_srs, &proofs, &inputs).unwrap();
+
+        let mut wrong_inputs = inputs.clone();
+        wrong_inputs[2][0] += Fr::one();
+        assert!(matches!(
+            verify_aggregate_proof(&verifier_srs, vk, &aggregate, &wrong_inputs),
+            Err(Groth16Error::InvalidProof)
+        ));
+        assert!(matches!(
+            verify_aggregate_proof(&verifier_srs, vk, &aggregate, &inputs[..2]),
+            Err(Groth16Error::ProofCountMismatch { .. })
+        ));
+
+        // an invalid proof cannot be hidden in the aggregate
+        proofs.swap(0, 1);
+        let aggregate = aggregate_proofs(&prover_srs, &proofs, &inputs).unwrap();
+        assert!(matches!(
+            verify_aggregate_proof(&verifier_srs, vk, &aggregate, &inputs),
+            Err(Groth16Error::InvalidProof)
+        ));
+        inputs.swap(0, 1);
+        let mut tampered = aggregate_proofs(&prover_srs, &proofs, &inputs).unwrap();
+        verify_aggregate_proof(&verifier_srs, vk, &tampered, &inputs).unwrap();
+        tampered.final_c = (tampered.final_c + vk.alpha_g1).into_affine();
+        assert!(matches!(
+            verify_aggregate_proof(&verifier_srs, vk, &tampered, &inputs),
+            Err(Groth16Error::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn aggregate_proof_rejects_tampered_rounds() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let srs = GenericSrs::<Bn254>::setup_insecure(&mut rng, 2);
+        let (prover_srs, verifier_srs) = srs.specialize(2).unwrap();
+        let vk = &circuit.pk.vk;
+        let (proofs, inputs) = proofs(&circuit, 2, &mut rng);
+        let aggregate = aggregate_proofs(&prover_srs, &proofs, &inputs).unwrap();
+
+        let mut tampered = aggregate.clone();
+        tampered.rounds = vec![aggregate.rounds[0].clone(); usize::BITS as usize - 1];
+        assert!(matches!(
+            verify_aggregate_proof(&verifier_srs, vk, &tampered, &inputs),
+            Err(Groth16Error::ProofCountMismatch { expected, actual: 2 }) if expected == 1 << (usize::BITS - 1)
+        ));
+
+        // the number of proofs overflows, also after a serialization round trip
+        tampered.rounds = vec![aggregate.rounds[0].clone(); usize::BITS as usize];
+        let mut bytes = Vec::new();
+        tampered.serialize_compressed(&mut bytes).unwrap();
+        let tampered = AggregateProof::<Bn254>::deserialize_compressed(bytes.as_slice()).unwrap();
+        let rounds = usize::BITS as usize;
+        assert!(matches!(
+            tampered.num_proofs(),
+            Err(Groth16Error::TooManyAggregationRounds(actual)) if actual == rounds
+        ));
+        assert!(matches!(
+            verify_aggregate_proof(&verifier_srs, vk, &tampered, &inputs),
+            Err(Groth16Error::TooManyAggregationRounds(actual)) if actual == rounds
+        ));
+    }
+
+    #[test]
+    fn srs_must_support_number_of_proofs() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let srs = GenericSrs::<Bn254>::setup_insecure(&mut rng, 3);
+        assert_eq!(srs.max_num_proofs(), 4);
+        assert!(matches!(
+            srs.specialize(5),
+            Err(Groth16Error::SrsTooSmall { max: 4, actual: 5 })
+        ));
+        let (prover_srs, _) = srs.specialize(2).unwrap();
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (proofs, inputs) = proofs(&circuit, 3, &mut rng);
+        assert!(matches!(
+            aggregate_proofs(&prover_srs, &proofs, &inputs),
+            Err(Groth16Error::SrsTooSmall { max: 2, actual: 3 })
+        ));
+        assert!(matches!(
+            aggregate_proofs::<Bn254, Vec<Fr>>(&prover_srs, &[], &[]),
+            Err(Groth16Error::EmptyAggregation)
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, PrimeGroup};
+use ark_ff::{Field, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::Rng;
+
+use crate::Groth16Error;
+
+/// A universal structured reference string for aggregating Groth16 proofs.
+///
+/// Consists of the powers `g^{alpha^i}`, `h^{alpha^i}`, `g^{beta^i}` and `h^{beta^i}` for two
+/// independent secrets `alpha` and `beta`, where `g` and `h` are the generators of G1 and G2. The
+/// SRS only depends on the pairing, not on the circuit, and can be loaded from a file with
+/// [`CanonicalDeserialize`]. Use [`GenericSrs::specialize`] to derive the keys for a number of
+/// proofs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GenericSrs<P: Pairing> {
+    g_alpha_powers: Vec<P::G1Affine>,
+    h_alpha_powers: Vec<P::G2Affine>,
+    g_beta_powers: Vec<P::G1Affine>,
+    h_beta_powers: Vec<P::G2Affine>,
+}
+
+/// The key used by the aggregator, see [`aggregate_proofs`](super::aggregate_proofs).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProverSrs<P: Pairing> {
+    pub(crate) n: usize,
+    /// `g^{alpha^i}` for `i < 2n`
+    pub(crate) g_alpha_powers: Vec<P::G1Affine>,
+    /// `g^{beta^i}` for `i < 2n`
+    pub(crate) g_beta_powers: Vec<P::G1Affine>,
+    /// `h^{alpha^i}` for `i < n`
+    pub(crate) h_alpha_powers: Vec<P::G2Affine>,
+    /// `h^{beta^i}` for `i < n`
+    pub(crate) h_beta_powers: Vec<P::G2Affine>,
+}
+
+/// The key used to verify aggregate proofs, see [`verify_aggregate_proof`](super::verify_aggregate_proof).
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierSrs<P: Pairing> {
+    pub(crate) g: P::G1Affine,
+    pub(crate) h: P::G2Affine,
+    pub(crate) g_alpha: P::G1Affine,
+    pub(crate) g_beta: P::G1Affine,
+    pub(crate) h_alpha: P::G2Affine,
+    pub(crate) h_beta: P::G2Affine,
+}
+
+fn powers<G: CurveGroup>(base: G, exponent: G::ScalarField, len: usize) -> Vec<G::Affine> {
+    let mut scalar = G::ScalarField::ONE;
+    let powers = (0..len)
+        .map(|_| {
+            let power = base * scalar;
+            scalar *= exponent;
+            power
+        })
+        .collect::<Vec<_>>();
+    G::normalize_batch(&powers)
+}
+
+impl<P: Pairing> GenericSrs<P> {
+    /// Samples a new SRS that supports aggregating up to `max_num_proofs` proofs.
+    ///
+    /// **This is insecure and only meant for testing**, as the caller learns the secrets `alpha`
+    /// and `beta`, which allows forging aggregate proofs. Production deployments must use an SRS
+    /// from a trusted setup ceremony.
+    pub fn setup_insecure<R: Rng>(rng: &mut R, max_num_proofs: usize) -> Self {
+        // the verifier needs at least the first power of each kind
+        let max_num_proofs = max_num_proofs.max(2).next_power_of_two();
+        let alpha = P::ScalarField::rand(rng);
+        let beta = P::ScalarField::rand(rng);
+        let g = P::G1::generator();
+        let h = P::G2::generator();
+        Self {
+            g_alpha_powers: powers(g, alpha, 2 * max_num_proofs),
+            h_alpha_powers: powers(h, alpha, max_num_proofs),
+            g_beta_powers: powers(g, beta, 2 * max_num_proofs),
+            h_beta_powers: powers(h, beta, max_num_proofs),
+        }
+    }
+
+    /// Returns the maximum number of proofs that can be aggregated with this SRS.
+    pub fn max_num_proofs(&self) -> usize {
+        let max = (self.g_alpha_powers.len().min(self.g_beta_powers.len()) / 2)
+            .min(self.h_alpha_powers.len())
+            .min(self.h_beta_powers.len());
+        // the verifier needs the first power of each kind and only powers of two are supported
+        if max < 2 { 0 } else { 1 << max.ilog2() }
+    }
+
+    /// Derives the keys for aggregating `num_proofs` proofs.
+    ///
+    /// The number of proofs is rounded up to the next power of two. Returns an error if the SRS is too small.
+    pub fn specialize(
+        &self,
+        num_proofs: usize,
+    ) -> Result<(ProverSrs<P>, VerifierSrs<P>), Groth16Error> {
+        let n = num_proofs.max(1).next_power_of_two();
+        let max = self.max_num_proofs();
+        if n > max {
+            return Err(Groth16Error::SrsTooSmall {
+                max,
+                actual: num_proofs,
+            });
+        }
+        let prover = ProverSrs {
+            n,
+            g_alpha_powers: self.g_alpha_powers[..2 * n].to_vec(),
+            g_beta_powers: self.g_beta_powers[..2 * n].to_vec(),
+            h_alpha_powers: self.h_alpha_powers[..n].to_vec(),
+            h_beta_powers: self.h_beta_powers[..n].to_vec(),
+        };
+        Ok((prover, self.verifier_srs()?))
+    }
+
+    /// Returns the [`VerifierSrs`], which does not depend on the number of proofs.
+    pub fn verifier_srs(&self) -> Result<VerifierSrs<P>, Groth16Error> {
+        if self.max_num_proofs() == 0 {
+            return Err(Groth16Error::SrsTooSmall { max: 0, actual: 1 });
+        }
+        Ok(VerifierSrs {
+            g: self.g_alpha_powers[0],
+            h: self.h_alpha_powers[0],
+            g_alpha: self.g_alpha_powers[1],
+            g_beta: self.g_beta_powers[1],
+            h_alpha: self.h_alpha_powers[1],
+            h_beta: self.h_beta_powers[1],
+        })
+    }
+}
+
+impl<P: Pairing> ProverSrs<P> {
+    /// Returns the number of proofs this key aggregates. Always a power of two.
+    pub fn num_proofs(&self) -> usize {
+        self.n
+    }
+}
@@ -0,0 +1,53 @@
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha512};
+
+/// A Fiat-Shamir transcript based on SHA-512.
+///
+/// Every message and challenge is absorbed into the running hash, so each challenge depends on
+/// the whole transcript up to that point.
+#[derive(Clone)]
+pub(crate) struct Transcript {
+    hasher: Sha512,
+}
+
+impl Transcript {
+    pub(crate) fn new(domain_separator: &[u8]) -> Self {
+        let mut transcript = Self {
+            hasher: Sha512::new(),
+        };
+        transcript.append_bytes(b"dst", domain_separator);
+        transcript
+    }
+
+    fn append_bytes(&mut self, label: &[u8], bytes: &[u8]) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+
+    pub(crate) fn append<T: CanonicalSerialize + ?Sized>(&mut self, label: &[u8], message: &T) {
+        let mut bytes = Vec::with_capacity(message.compressed_size());
+        message
+            .serialize_compressed(&mut bytes)
+            .expect("can serialize into Vec");
+        self.append_bytes(label, &bytes);
+    }
+
+    /// Derives a non-zero challenge and absorbs it into the transcript.
+    pub(crate) fn challenge<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        let mut counter = 0u32;
+        loop {
+            let mut hasher = self.hasher.clone();
+            hasher.update(label);
+            hasher.update(counter.to_le_bytes());
+            let challenge = F::from_le_bytes_mod_order(&hasher.finalize());
+            if !challenge.is_zero() {
+                self.append(label, &challenge);
+                return challenge;
+            }
+            counter += 1;
+        }
+    }
+}
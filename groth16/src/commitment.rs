@@ -0,0 +1,647 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::{
+    ConstraintMatrices, Groth16, Groth16Error, Groth16Verifier, ProverContext, ProvingKey,
+    R1CSToQAP, VerifyingKey,
+};
+
+/// A hash function mapping the serialized commitments to field elements.
+///
+/// gnark derives the value of a commitment wire by hashing the commitment and the committed public
+/// inputs. The prover and verifier must use the same function. By default, gnark uses
+/// `expand_message_xmd` of RFC 9380, see [`gnark_hash_to_field`]. For proofs verified by the generated
+/// Solidity verifier, this is the hash of the Solidity verifier (Keccak-256 or SHA-256) interpreted
+/// as a big-endian integer and reduced modulo the field order, see [`sha256_hash_to_field`].
+///
+/// The trait is implemented for closures mapping bytes to field elements.
+pub trait HashToField<F>: Sync {
+    /// Hashes `message` to a field element.
+    fn hash_to_field(&self, message: &[u8]) -> F;
+}
+
+impl<F, H> HashToField<F> for H
+where
+    H: Fn(&[u8]) -> F + Sync,
+{
+    fn hash_to_field(&self, message: &[u8]) -> F {
+        self(message)
+    }
+}
+
+/// Hashes `message` with SHA-256 and reduces the big-endian digest modulo the field order.
+///
+/// This matches gnark's `WithHashToFieldFunction(sha256.New())` and the `sha256` option of the Solidity verifier.
+pub fn sha256_hash_to_field<F: PrimeField>(message: &[u8]) -> F {
+    F::from_be_bytes_mod_order(&Sha256::digest(message))
+}
+
+/// The domain separation tag of gnark's default hash for the commitment wires.
+pub const GNARK_COMMITMENT_DST: &[u8] = b"bsb22-commitment";
+
+/// Hashes `message` to a field element with `expand_message_xmd` and SHA-256 and the domain separation tag
+/// [`GNARK_COMMITMENT_DST`].
+///
+/// This is the default hash of gnark's `api.Commit` if no `WithHashToFieldFunction` option is set.
+pub fn gnark_hash_to_field<F: PrimeField>(message: &[u8]) -> F {
+    expand_message_xmd_hash_to_field(GNARK_COMMITMENT_DST, message)
+}
+
+/// Hashes `message` to a field element like `hash_to_field` of [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380#section-5.2)
+/// with `expand_message_xmd`, SHA-256 and a security parameter of 128 bits.
+pub fn expand_message_xmd_hash_to_field<F: PrimeField>(dst: &[u8], message: &[u8]) -> F {
+    hash_to_field_xmd(dst, message, 1)[0]
+}
+
+/// Hashes `message` to `count` field elements, see [`expand_message_xmd_hash_to_field`].
+fn hash_to_field_xmd<F: PrimeField>(dst: &[u8], message: &[u8], count: usize) -> Vec<F> {
+    // L = ceil((ceil(log2(p)) + k) / 8) for the security parameter k = 128
+    let len = (F::MODULUS_BIT_SIZE as usize + 128).div_ceil(8);
+    expand_message_xmd(dst, message, count * len)
+        .chunks_exact(len)
+        .map(F::from_be_bytes_mod_order)
+        .collect()
+}
+
+/// Expands `message` to `len` uniform bytes with `expand_message_xmd` and SHA-256, see
+/// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380#section-5.3.1).
+///
+/// # Panics
+///
+/// Panics if `len` exceeds `255 * 32` bytes.
+fn expand_message_xmd(dst: &[u8], message: &[u8], len: usize) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 64;
+    let ell = len.div_ceil(Sha256::output_size());
+    assert!(ell <= 255, "expand_message_xmd supports at most 8160 bytes");
+    // long tags are replaced by their hash, see section 5.3.3
+    let dst = if dst.len() > 255 {
+        Sha256::new()
+            .chain_update(b"H2C-OVERSIZE-DST-")
+            .chain_update(dst)
+            .finalize()
+            .to_vec()
+    } else {
+        dst.to_vec()
+    };
+    let dst_prime = [dst.as_slice(), &[dst.len() as u8]].concat();
+
+    let b_0 = Sha256::new()
+        .chain_update([0; BLOCK_SIZE])
+        .chain_update(message)
+        .chain_update((len as u16).to_be_bytes())
+        .chain_update([0])
+        .chain_update(&dst_prime)
+        .finalize();
+    let mut uniform_bytes = Vec::with_capacity(ell * Sha256::output_size());
+    let mut b_i = Sha256::new()
+        .chain_update(b_0)
+        .chain_update([1])
+        .chain_update(&dst_prime)
+        .finalize();
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let xor = b_0.iter().zip(&b_i).map(|(a, b)| a ^ b).collect::<Vec<_>>();
+        b_i = Sha256::new()
+            .chain_update(xor)
+            .chain_update([i as u8])
+            .chain_update(&dst_prime)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len);
+    uniform_bytes
+}
+
+/// The proving key of a gnark-style (BSB22) Pedersen commitment to a subset of the witness.
+///
+/// The committed private witness values are not part of the `l_query` of the proving key (their
+/// entries are zero). Instead, their contribution is committed with the `basis`, which is scaled by
+/// `1/gamma` like the public inputs. The value of the commitment wire is the hash of the
+/// commitment and the committed public inputs, see [`HashToField`].
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentProvingKey<P: Pairing> {
+    /// The bases for the committed private witness values.
+    pub basis: Vec<P::G1Affine>,
+    /// The bases scaled by the secret `sigma`, used for the proof of knowledge.
+    pub basis_exp_sigma: Vec<P::G1Affine>,
+    /// The indices of the committed public inputs (starting at 0 for the first public input).
+    pub public_committed: Vec<usize>,
+    /// The indices of the committed private values in the full witness.
+    pub private_committed: Vec<usize>,
+}
+
+/// The verifying key of gnark-style (BSB22) Pedersen commitments, see [`CommitmentProvingKey`].
+///
+/// Like in gnark, all commitments of a circuit share the same `g` and `sigma`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentVerifyingKey<P: Pairing> {
+    /// The G2 generator of the commitment keys.
+    pub g: P::G2Affine,
+    /// `-sigma * g`
+    pub g_sigma_neg: P::G2Affine,
+    /// The indices of the committed public inputs of every commitment (starting at 0 for the first public input).
+    pub public_committed: Vec<Vec<usize>>,
+}
+
+/// A Groth16 proof with gnark-style (BSB22) commitments to parts of the witness.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofWithCommitments<P: Pairing> {
+    /// The Groth16 proof.
+    pub proof: ark_groth16::Proof<P>,
+    /// One Pedersen commitment per commitment key.
+    pub commitments: Vec<P::G1Affine>,
+    /// The batched proof of knowledge of the committed values.
+    pub commitment_pok: P::G1Affine,
+}
+
+/// The Pedersen commitments to a witness and the values of the commitment wires, computed by [`Groth16::commit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commitments<P: Pairing> {
+    /// One Pedersen commitment per commitment key.
+    pub commitments: Vec<P::G1Affine>,
+    /// The values of the commitment wires.
+    pub hashes: Vec<P::ScalarField>,
+}
+
+/// Serializes a field element as 32 byte big-endian integer, like `abi.encodePacked` in Solidity.
+fn write_field<F: PrimeField>(buf: &mut Vec<u8>, value: &F) {
+    buf.extend(value.into_bigint().to_bytes_be());
+}
+
+/// Serializes an affine point as big-endian `x || y`, with the point at infinity as `(0, 0)`.
+fn write_point<G: AffineRepr>(buf: &mut Vec<u8>, point: &G) {
+    let (x, y) = point.xy().unwrap_or_default();
+    for coordinate in x
+        .to_base_prime_field_elements()
+        .chain(y.to_base_prime_field_elements())
+    {
+        write_field(buf, &coordinate);
+    }
+}
+
+/// Computes the value of the commitment wire: the hash of the commitment and the committed public inputs.
+pub(crate) fn commitment_hash<P, H>(
+    commitment: &P::G1Affine,
+    public_committed: &[usize],
+    public_inputs: &[P::ScalarField],
+    hasher: &H,
+) -> Result<P::ScalarField, Groth16Error>
+where
+    P: Pairing,
+    H: HashToField<P::ScalarField> + ?Sized,
+{
+    let mut prehash = Vec::new();
+    write_point(&mut prehash, commitment);
+    for idx in public_committed {
+        let input = public_inputs
+            .get(*idx)
+            .ok_or(Groth16Error::InvalidPublicInputLength {
+                expected: idx + 1,
+                actual: public_inputs.len(),
+            })?;
+        write_field(&mut prehash, input);
+    }
+    Ok(hasher.hash_to_field(&prehash))
+}
+
+/// Derives the challenge for batching the proofs of knowledge from the commitment hashes.
+///
+/// A single proof of knowledge is not batched, so no challenge is needed.
+pub(crate) fn pok_challenge<F: PrimeField, H: HashToField<F> + ?Sized>(
+    hashes: &[F],
+    hasher: &H,
+) -> F {
+    if hashes.len() <= 1 {
+        return F::ONE;
+    }
+    let mut prehash = Vec::with_capacity(hashes.len() * 32);
+    for hash in hashes {
+        write_field(&mut prehash, hash);
+    }
+    hasher.hash_to_field(&prehash)
+}
+
+/// Computes `sum_i challenge^i * points[i]`.
+pub(crate) fn fold<G: CurveGroup>(points: &[G::Affine], challenge: G::ScalarField) -> G {
+    let powers = std::iter::successors(Some(G::ScalarField::ONE), |p| Some(*p * challenge))
+        .take(points.len())
+        .collect::<Vec<_>>();
+    G::msm_unchecked(points, &powers)
+}
+
+/// Collects the committed private values of the witness.
+fn committed_values<F: Copy>(key_indices: &[usize], witness: &[F]) -> Result<Vec<F>, Groth16Error> {
+    key_indices
+        .iter()
+        .map(|idx| {
+            witness
+                .get(*idx)
+                .copied()
+                .ok_or(Groth16Error::InvalidWitnessLength {
+                    expected: idx + 1,
+                    actual: witness.len(),
+                })
+        })
+        .collect()
+}
+
+impl<P: Pairing> Groth16<P> {
+    /// Computes the Pedersen commitments to the witness and the values of the commitment wires.
+    ///
+    /// The values of the commitment wires (the hashes of the commitments and the committed public inputs) must be part of the
+    /// witness passed to [`Groth16::prove_with_commitments`]. If a commitment commits to the wire of an earlier commitment, the
+    /// value of that wire is read from the witness.
+    pub fn commit<H: HashToField<P::ScalarField> + ?Sized>(
+        keys: &[CommitmentProvingKey<P>],
+        witness: &[P::ScalarField],
+        hasher: &H,
+    ) -> Result<Commitments<P>, Groth16Error> {
+        let public_inputs = witness.get(1..).unwrap_or_default();
+        let mut commitments = Vec::with_capacity(keys.len());
+        let mut hashes = Vec::with_capacity(keys.len());
+        for key in keys {
+            let values = committed_values(&key.private_committed, witness)?;
+            let commitment = P::G1::msm(&key.basis, &values)
+                .map_err(|_| Groth16Error::InvalidCommitment)?
+                .into_affine();
+            hashes.push(commitment_hash::<P, _>(
+                &commitment,
+                &key.public_committed,
+                public_inputs,
+                hasher,
+            )?);
+            commitments.push(commitment);
+        }
+        Ok(Commitments {
+            commitments,
+            hashes,
+        })
+    }
+
+    /// Creates a Groth16 proof with gnark-style (BSB22) Pedersen commitments to parts of the witness.
+    ///
+    /// The commitment wires must be the last `keys.len()` instance variables and the witness must contain the values computed by
+    /// [`Groth16::commit`], otherwise [`Groth16Error::CommitmentHashMismatch`] is returned. The proving key must not contain the
+    /// committed private values in its `l_query`, see [`CommitmentProvingKey`].
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(level = "debug", name = "Groth16 - Proof with commitments", skip_all)]
+    pub fn prove_with_commitments<R: R1CSToQAP, H: HashToField<P::ScalarField> + ?Sized>(
+        ctx: &ProverContext<P, R>,
+        pkey: &ProvingKey<P>,
+        keys: &[CommitmentProvingKey<P>],
+        r: P::ScalarField,
+        s: P::ScalarField,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+        hasher: &H,
+    ) -> Result<ProofWithCommitments<P>, Groth16Error> {
+        let num_public = matrices.num_instance_variables - 1;
+        if keys.len() > num_public {
+            return Err(Groth16Error::CommitmentCountMismatch {
+                expected: num_public,
+                actual: keys.len(),
+            });
+        }
+        let Commitments {
+            commitments,
+            hashes,
+        } = Self::commit(keys, witness, hasher)?;
+        let first_wire = matrices.num_instance_variables - keys.len();
+        for (i, hash) in hashes.iter().enumerate() {
+            if witness.get(first_wire + i) != Some(hash) {
+                return Err(Groth16Error::CommitmentHashMismatch(i));
+            }
+        }
+
+        let proof = Self::prove_with_context(ctx, pkey, r, s, matrices, witness)?;
+
+        let mut poks = Vec::with_capacity(keys.len());
+        for key in keys {
+            let values = committed_values(&key.private_committed, witness)?;
+            let pok = P::G1::msm(&key.basis_exp_sigma, &values)
+                .map_err(|_| Groth16Error::InvalidCommitment)?;
+            poks.push(pok.into_affine());
+        }
+        let commitment_pok = fold::<P::G1>(&poks, pok_challenge(&hashes, hasher)).into_affine();
+        Ok(ProofWithCommitments {
+            proof,
+            commitments,
+            commitment_pok,
+        })
+    }
+
+    /// Verifies a Groth16 proof with gnark-style (BSB22) Pedersen commitments.
+    ///
+    /// The public inputs do not contain the values of the commitment wires, they are recomputed from the commitments.
+    /// This method prepares the verifying key on every call. Use a [`Groth16Verifier`] to verify many proofs under the same key.
+    pub fn verify_with_commitments<H: HashToField<P::ScalarField> + ?Sized>(
+        vk: &VerifyingKey<P>,
+        commitment_vk: &CommitmentVerifyingKey<P>,
+        proof: &ProofWithCommitments<P>,
+        public_inputs: &[P::ScalarField],
+        hasher: &H,
+    ) -> Result<(), Groth16Error> {
+        Groth16Verifier::new(vk)?.verify_with_commitments(
+            commitment_vk,
+            proof,
+            public_inputs,
+            hasher,
+        )
+    }
+}
+
+impl<P: Pairing> Groth16Verifier<P> {
+    /// Verifies a Groth16 proof with gnark-style (BSB22) Pedersen commitments against the prepared verifying key.
+    ///
+    /// See [`Groth16::verify_with_commitments`].
+    pub fn verify_with_commitments<H: HashToField<P::ScalarField> + ?Sized>(
+        &self,
+        commitment_vk: &CommitmentVerifyingKey<P>,
+        proof: &ProofWithCommitments<P>,
+        public_inputs: &[P::ScalarField],
+        hasher: &H,
+    ) -> Result<(), Groth16Error> {
+        let num_commitments = commitment_vk.public_committed.len();
+        if proof.commitments.len() != num_commitments {
+            return Err(Groth16Error::CommitmentCountMismatch {
+                expected: num_commitments,
+                actual: proof.commitments.len(),
+            });
+        }
+        let expected = self.num_public_inputs().saturating_sub(num_commitments);
+        if public_inputs.len() != expected {
+            return Err(Groth16Error::InvalidPublicInputLength {
+                expected,
+                actual: public_inputs.len(),
+            });
+        }
+
+        let mut inputs = public_inputs.to_vec();
+        for (commitment, committed) in proof
+            .commitments
+            .iter()
+            .zip(&commitment_vk.public_committed)
+        {
+            let hash = commitment_hash::<P, _>(commitment, committed, &inputs, hasher)?;
+            inputs.push(hash);
+        }
+
+        if num_commitments > 0 {
+            let challenge = pok_challenge(&inputs[expected..], hasher);
+            let folded = fold::<P::G1>(&proof.commitments, challenge);
+            let check = P::multi_pairing(
+                [folded.into_affine(), proof.commitment_pok],
+                [commitment_vk.g_sigma_neg, commitment_vk.g],
+            );
+            if !check.is_zero() {
+                return Err(Groth16Error::InvalidCommitment);
+            }
+        }
+
+        let committed = proof
+            .commitments
+            .iter()
+            .fold(P::G1::zero(), |acc, c| acc + c);
+        self.verify_with_offset(&proof.proof, &inputs, committed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fr};
+    use ark_ec::PrimeGroup;
+    use ark_ff::{UniformRand, Zero};
+    use ark_relations::{
+        lc,
+        r1cs::{
+            ConstraintMatrices, ConstraintSynthesizer, ConstraintSystemRef, SynthesisError,
+            Variable,
+        },
+    };
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::{Groth16, LibSnarkReduction, ProverContext, ProvingKey};
+
+    /// Commits to the private `a` and `b` and the public `c = a * b`, and proves `(a + h) * b = t`
+    /// for the commitment wire `h`.
+    #[derive(Clone, Copy)]
+    struct CommitCircuit {
+        a: Fr,
+        b: Fr,
+        h: Fr,
+    }
+
+    impl ConstraintSynthesizer<Fr> for CommitCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let c = cs.new_input_variable(|| Ok(self.a * self.b))?;
+            let h = cs.new_input_variable(|| Ok(self.h))?;
+            let a = cs.new_witness_variable(|| Ok(self.a))?;
+            let b = cs.new_witness_variable(|| Ok(self.b))?;
+            let t = cs.new_witness_variable(|| Ok((self.a + self.h) * self.b))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+            cs.enforce_constraint(lc!() + a + h, lc!() + b, lc!() + t)?;
+            cs.enforce_constraint(lc!() + t, lc!() + Variable::One, lc!() + t)?;
+            Ok(())
+        }
+    }
+
+    struct Setup {
+        pk: ProvingKey<Bn254>,
+        matrices: ConstraintMatrices<Fr>,
+        commitment_pk: CommitmentProvingKey<Bn254>,
+        commitment_vk: CommitmentVerifyingKey<Bn254>,
+    }
+
+    /// Moves the committed private wires `a` and `b` from the `l_query` to the commitment basis.
+    fn setup(rng: &mut impl ark_std::rand::RngCore) -> Setup {
+        let circuit = CommitCircuit {
+            a: Fr::zero(),
+            b: Fr::zero(),
+            h: Fr::zero(),
+        };
+        let (gamma, delta, sigma) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let mut pk = ark_groth16::Groth16::<Bn254>::generate_parameters_with_qap(
+            circuit,
+            Fr::rand(rng),
+            Fr::rand(rng),
+            gamma,
+            delta,
+            ark_bn254::G1Projective::generator(),
+            ark_bn254::G2Projective::generator(),
+            rng,
+        )
+        .unwrap();
+        let cs = ark_relations::r1cs::ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.finalize();
+        let matrices = cs.to_matrices().unwrap();
+
+        // the witness is [1, c, h, a, b, t], the l_query covers [a, b, t]
+        let private_committed = vec![3, 4];
+        let scale = delta * gamma.inverse().unwrap();
+        let mut basis = Vec::new();
+        for idx in &private_committed {
+            let l = &mut pk.l_query[idx - matrices.num_instance_variables];
+            basis.push((*l * scale).into_affine());
+            *l = ark_bn254::G1Affine::zero();
+        }
+        let basis_exp_sigma = basis.iter().map(|b| (*b * sigma).into_affine()).collect();
+        let g = ark_bn254::G2Affine::generator();
+        Setup {
+            pk,
+            matrices,
+            commitment_pk: CommitmentProvingKey {
+                basis,
+                basis_exp_sigma,
+                public_committed: vec![0],
+                private_committed,
+            },
+            commitment_vk: CommitmentVerifyingKey {
+                g,
+                g_sigma_neg: (-(g * sigma)).into_affine(),
+                public_committed: vec![vec![0]],
+            },
+        }
+    }
+
+    fn witness(setup: &Setup, a: Fr, b: Fr) -> Vec<Fr> {
+        witness_with_hasher(setup, a, b, &sha256_hash_to_field)
+    }
+
+    fn witness_with_hasher(setup: &Setup, a: Fr, b: Fr, hasher: &dyn HashToField<Fr>) -> Vec<Fr> {
+        let mut witness = vec![Fr::from(1), a * b, Fr::zero(), a, b, Fr::zero()];
+        let hashes =
+            Groth16::<Bn254>::commit(std::slice::from_ref(&setup.commitment_pk), &witness, hasher)
+                .unwrap()
+                .hashes;
+        witness[2] = hashes[0];
+        witness[5] = (a + hashes[0]) * b;
+        witness
+    }
+
+    #[test]
+    fn proof_with_commitments_verifies() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let setup = setup(&mut rng);
+        let hashers: [&dyn HashToField<Fr>; 2] =
+            [&sha256_hash_to_field::<Fr>, &gnark_hash_to_field::<Fr>];
+        for hasher in hashers {
+            let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+            let witness = witness_with_hasher(&setup, a, b, hasher);
+            let ctx =
+                ProverContext::<Bn254, LibSnarkReduction>::new(&setup.pk, &setup.matrices).unwrap();
+            let proof = Groth16::prove_with_commitments(
+                &ctx,
+                &setup.pk,
+                std::slice::from_ref(&setup.commitment_pk),
+                Fr::rand(&mut rng),
+                Fr::rand(&mut rng),
+                &setup.matrices,
+                &witness,
+                hasher,
+            )
+            .unwrap();
+            let keys = &setup.commitment_vk;
+            Groth16::verify_with_commitments(&setup.pk.vk, keys, &proof, &[a * b], hasher).unwrap();
+
+            // the commitment binds the public input
+            assert!(matches!(
+                Groth16::verify_with_commitments(
+                    &setup.pk.vk,
+                    keys,
+                    &proof,
+                    &[a * b + Fr::from(1)],
+                    hasher,
+                ),
+                Err(Groth16Error::InvalidProof)
+            ));
+            // the commitment cannot be changed without the proof of knowledge
+            let mut tampered = proof.clone();
+            tampered.commitments[0] =
+                (tampered.commitments[0] + setup.commitment_pk.basis[0]).into();
+            assert!(matches!(
+                Groth16::verify_with_commitments(&setup.pk.vk, keys, &tampered, &[a * b], hasher),
+                Err(Groth16Error::InvalidCommitment)
+            ));
+        }
+    }
+
+    #[test]
+    fn expand_message_xmd_matches_rfc_9380() {
+        // the expand_message_xmd(SHA-256) vectors of appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            expand_message_xmd(dst, b"", 0x20),
+            [
+                0x68, 0xa9, 0x85, 0xb8, 0x7e, 0xb6, 0xb4, 0x69, 0x52, 0x12, 0x89, 0x11, 0xf2, 0xa4,
+                0x41, 0x2b, 0xbc, 0x30, 0x2a, 0x9d, 0x75, 0x96, 0x67, 0xf8, 0x7f, 0x7a, 0x21, 0xd8,
+                0x03, 0xf0, 0x72, 0x35
+            ]
+        );
+        assert_eq!(
+            expand_message_xmd(dst, b"abc", 0x20),
+            [
+                0xd8, 0xcc, 0xab, 0x23, 0xb5, 0x98, 0x5c, 0xce, 0xa8, 0x65, 0xc6, 0xc9, 0x7b, 0x6e,
+                0x5b, 0x83, 0x50, 0xe7, 0x94, 0xe6, 0x03, 0xb4, 0xb9, 0x79, 0x02, 0xf5, 0x3a, 0x8a,
+                0x0d, 0x60, 0x56, 0x15
+            ]
+        );
+
+        // the field elements u of the BLS12381G1_XMD:SHA-256_SSWU_RO_ suite of appendix J.9.1
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        assert_eq!(
+            hash_to_field_xmd::<ark_bls12_381::Fq>(dst, b"", 2),
+            [
+                ark_ff::MontFp!(
+                    "1790030616568561980207134218344899338736900885118493183248255875682123737756800213955590674957414534085508415116879"
+                ),
+                ark_ff::MontFp!(
+                    "247470258331762152370823329280394074170045058453263356372873997375166908584899100434893060702108665825589810322121"
+                ),
+            ]
+        );
+        assert_eq!(
+            hash_to_field_xmd::<ark_bls12_381::Fq>(dst, b"abc", 2),
+            [
+                ark_ff::MontFp!(
+                    "2088728490498894818688784437928579501848367107744050576780266498473771518428420173373487118890161663886009635645777"
+                ),
+                ark_ff::MontFp!(
+                    "32138924938310862093169606408734331410171587925844216752733293543601988453843327878077294514665889481436558332217"
+                ),
+            ]
+        );
+        assert_eq!(
+            expand_message_xmd_hash_to_field::<Fr>(GNARK_COMMITMENT_DST, b"abc"),
+            gnark_hash_to_field::<Fr>(b"abc")
+        );
+    }
+
+    #[test]
+    fn prover_rejects_wrong_commitment_wire() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let setup = setup(&mut rng);
+        let mut witness = witness(&setup, Fr::rand(&mut rng), Fr::rand(&mut rng));
+        witness[2] += Fr::from(1);
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&setup.pk, &setup.matrices).unwrap();
+        assert!(matches!(
+            Groth16::prove_with_commitments(
+                &ctx,
+                &setup.pk,
+                std::slice::from_ref(&setup.commitment_pk),
+                Fr::from(1),
+                Fr::from(1),
+                &setup.matrices,
+                &witness,
+                &sha256_hash_to_field,
+            ),
+            Err(Groth16Error::CommitmentHashMismatch(0))
+        ));
+    }
+}
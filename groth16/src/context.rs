@@ -0,0 +1,180 @@
+use ark_ec::pairing::Pairing;
+use ark_groth16::ProvingKey;
+use ark_relations::r1cs::ConstraintMatrices;
+
+use crate::{G1Query, Groth16Error, ProvingKeySource, R1CSToQAP};
+
+/// Witness independent data for proving a fixed circuit with the reduction `R`.
+///
+/// Building the [`R1CSToQAP::Domain`] (evaluation domain, roots of unity and their powers) only
+/// depends on the size of the constraint system. When proving the same circuit many times, create
+/// a `ProverContext` once and pass it to [`Groth16::prove_with_context`](crate::Groth16::prove_with_context).
+#[derive(Debug)]
+pub struct ProverContext<P: Pairing, R: R1CSToQAP> {
+    num_constraints: usize,
+    num_instance_variables: usize,
+    num_witness_variables: usize,
+    domain: R::Domain<P::ScalarField>,
+}
+
+impl<P: Pairing, R: R1CSToQAP> Clone for ProverContext<P, R> {
+    fn clone(&self) -> Self {
+        Self {
+            num_constraints: self.num_constraints,
+            num_instance_variables: self.num_instance_variables,
+            num_witness_variables: self.num_witness_variables,
+            domain: self.domain.clone(),
+        }
+    }
+}
+
+impl<P: Pairing, R: R1CSToQAP> ProverContext<P, R> {
+    /// Creates a new `ProverContext` for the circuit described by the proving key and constraint matrices.
+    ///
+    /// Returns an error if the proving key does not match the constraint matrices or the constraint system does not fit into an evaluation domain.
+    pub fn new(
+        pkey: &ProvingKey<P>,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+    ) -> Result<Self, Groth16Error> {
+        Self::from_source(pkey, matrices)
+    }
+
+    /// Creates a new `ProverContext` for the circuit described by the [`ProvingKeySource`] and constraint matrices.
+    ///
+    /// See [`ProverContext::new`].
+    pub fn from_source<S: ProvingKeySource<P>>(
+        source: &S,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+    ) -> Result<Self, Groth16Error> {
+        let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+        let a_query_len = source.g1_query_len(G1Query::A);
+        if a_query_len != num_variables {
+            return Err(Groth16Error::ProvingKeyMismatch {
+                expected: num_variables,
+                actual: a_query_len,
+            });
+        }
+        let l_query_len = source.g1_query_len(G1Query::L);
+        if l_query_len != matrices.num_witness_variables {
+            return Err(Groth16Error::ProvingKeyMismatch {
+                expected: matrices.num_witness_variables,
+                actual: l_query_len,
+            });
+        }
+        let domain = R::domain(matrices.num_constraints, matrices.num_instance_variables)?;
+        Ok(Self {
+            num_constraints: matrices.num_constraints,
+            num_instance_variables: matrices.num_instance_variables,
+            num_witness_variables: matrices.num_witness_variables,
+            domain,
+        })
+    }
+
+    /// Returns the precomputed [`R1CSToQAP::Domain`].
+    pub fn domain(&self) -> &R::Domain<P::ScalarField> {
+        &self.domain
+    }
+
+    pub(crate) fn check_matrices(
+        &self,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+    ) -> Result<(), Groth16Error> {
+        if self.num_constraints != matrices.num_constraints
+            || self.num_instance_variables != matrices.num_instance_variables
+            || self.num_witness_variables != matrices.num_witness_variables
+        {
+            return Err(Groth16Error::ContextMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::test_utils::TestCircuit;
+    use crate::{CircomReduction, Groth16, LibSnarkReduction};
+
+    #[test]
+    fn prove_with_context_matches_prove() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        for _ in 0..2 {
+            let is_proof = Groth16::prove_with_context(
+                &ctx,
+                &circuit.pk,
+                r,
+                s,
+                &circuit.matrices,
+                &circuit.witness,
+            )
+            .unwrap();
+            let should_proof = Groth16::prove::<LibSnarkReduction>(
+                &circuit.pk,
+                r,
+                s,
+                &circuit.matrices,
+                &circuit.witness,
+            )
+            .unwrap();
+            assert_eq!(is_proof, should_proof);
+            Groth16::verify(&circuit.pk.vk, &is_proof, circuit.public_inputs()).unwrap();
+        }
+
+        let ctx =
+            ProverContext::<Bn254, CircomReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        let is_proof = Groth16::prove_with_context(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        let should_proof = Groth16::prove::<CircomReduction>(
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        assert_eq!(is_proof, should_proof);
+    }
+
+    #[test]
+    fn context_rejects_mismatching_matrices() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let mut matrices = circuit.matrices.clone();
+        matrices.num_witness_variables += 1;
+        assert!(matches!(
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &matrices),
+            Err(Groth16Error::ProvingKeyMismatch { .. })
+        ));
+
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        matrices = circuit.matrices.clone();
+        matrices.num_constraints += 1;
+        assert!(matches!(
+            Groth16::prove_with_context(
+                &ctx,
+                &circuit.pk,
+                ark_bn254::Fr::from(1),
+                ark_bn254::Fr::from(1),
+                &matrices,
+                &circuit.witness,
+            ),
+            Err(Groth16Error::ContextMismatch)
+        ));
+    }
+}
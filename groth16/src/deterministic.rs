@@ -0,0 +1,51 @@
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha512};
+
+/// Domain separation tag for the derivation of `r` and `s`.
+const DST: &[u8] = b"TACEO-GROTH16-DETERMINISTIC-RS-V1";
+
+/// Hashes the length and the uncompressed serialization of every element of the witness.
+fn hash_witness<F: PrimeField>(witness: &[F]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    hasher.update((witness.len() as u64).to_le_bytes());
+    let mut buf = Vec::with_capacity(F::zero().uncompressed_size());
+    for w in witness {
+        buf.clear();
+        w.serialize_uncompressed(&mut buf)
+            .expect("can serialize field element into Vec");
+        hasher.update(&buf);
+    }
+    hasher.finalize().into()
+}
+
+/// Derives a non-zero field element from the seed and the witness hash for the given label.
+///
+/// The 512 bit output of SHA-512 is reduced modulo the field order, so the bias is negligible.
+/// Similar to RFC 6979, a counter is incremented until the result is non-zero.
+fn derive_scalar<F: PrimeField>(seed: &[u8; 32], witness_hash: &[u8; 64], label: u8) -> F {
+    let mut counter = 0u32;
+    loop {
+        let digest = Sha512::new()
+            .chain_update([DST.len() as u8])
+            .chain_update(DST)
+            .chain_update([label])
+            .chain_update(seed)
+            .chain_update(witness_hash)
+            .chain_update(counter.to_le_bytes())
+            .finalize();
+        let scalar = F::from_le_bytes_mod_order(&digest);
+        if !scalar.is_zero() {
+            return scalar;
+        }
+        counter += 1;
+    }
+}
+
+/// Derives the randomness `r` and `s` from the seed and the witness.
+pub(crate) fn derive_randomness<F: PrimeField>(seed: &[u8; 32], witness: &[F]) -> (F, F) {
+    let witness_hash = hash_witness(witness);
+    (
+        derive_scalar(seed, &witness_hash, b'r'),
+        derive_scalar(seed, &witness_hash, b's'),
+    )
+}
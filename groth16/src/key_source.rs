@@ -0,0 +1,504 @@
+use std::borrow::Cow;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::{Mutex, PoisonError};
+
+use ark_ec::AffineRepr;
+use ark_ec::pairing::Pairing;
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+
+use crate::Groth16Error;
+
+/// The G1 queries of a Groth16 [`ProvingKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum G1Query {
+    /// The `a_query`, one element per variable.
+    A,
+    /// The `b_g1_query`, one element per variable.
+    BG1,
+    /// The `l_query`, one element per witness variable.
+    L,
+    /// The `h_query`, one element per power of the QAP witness.
+    H,
+}
+
+/// A source of the elements of a Groth16 proving key.
+///
+/// The prover only reads the queries of the proving key in chunks through this trait, see
+/// [`Groth16::prove_streaming`](crate::Groth16::prove_streaming). This allows to prove with keys
+/// that are too large to be kept in memory, e.g., by reading the queries from a memory-mapped file.
+/// Implementations backed by memory return [`Cow::Borrowed`] slices, implementations backed by a
+/// file return [`Cow::Owned`] chunks that are dropped after the chunk was processed.
+///
+/// The trait is implemented for [`ProvingKey`] and for [`ProvingKeyReader`], which reads the queries from a serialized
+/// [`ProvingKey`].
+pub trait ProvingKeySource<P: Pairing>: Sync {
+    /// Returns the verifying key of the proving key.
+    fn verifying_key(&self) -> &VerifyingKey<P>;
+
+    /// Returns `beta` in G1.
+    fn beta_g1(&self) -> P::G1Affine;
+
+    /// Returns `delta` in G1.
+    fn delta_g1(&self) -> P::G1Affine;
+
+    /// Returns the number of elements of the G1 query.
+    fn g1_query_len(&self, query: G1Query) -> usize;
+
+    /// Returns the number of elements of the `b_g2_query`.
+    fn b_g2_query_len(&self) -> usize;
+
+    /// Returns the elements of the G1 query in `range`.
+    fn g1_query(
+        &self,
+        query: G1Query,
+        range: Range<usize>,
+    ) -> Result<Cow<'_, [P::G1Affine]>, Groth16Error>;
+
+    /// Returns the elements of the `b_g2_query` in `range`.
+    fn b_g2_query(&self, range: Range<usize>) -> Result<Cow<'_, [P::G2Affine]>, Groth16Error>;
+}
+
+fn get_range<T: Clone>(query: &[T], range: Range<usize>) -> Result<Cow<'_, [T]>, Groth16Error> {
+    let len = query.len();
+    query.get(range.clone()).map(Cow::Borrowed).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("range {range:?} out of bounds for query of length {len}"),
+        )
+        .into()
+    })
+}
+
+impl<P: Pairing> ProvingKeySource<P> for ProvingKey<P> {
+    fn verifying_key(&self) -> &VerifyingKey<P> {
+        &self.vk
+    }
+
+    fn beta_g1(&self) -> P::G1Affine {
+        self.beta_g1
+    }
+
+    fn delta_g1(&self) -> P::G1Affine {
+        self.delta_g1
+    }
+
+    fn g1_query_len(&self, query: G1Query) -> usize {
+        match query {
+            G1Query::A => self.a_query.len(),
+            G1Query::BG1 => self.b_g1_query.len(),
+            G1Query::L => self.l_query.len(),
+            G1Query::H => self.h_query.len(),
+        }
+    }
+
+    fn b_g2_query_len(&self) -> usize {
+        self.b_g2_query.len()
+    }
+
+    fn g1_query(
+        &self,
+        query: G1Query,
+        range: Range<usize>,
+    ) -> Result<Cow<'_, [P::G1Affine]>, Groth16Error> {
+        match query {
+            G1Query::A => get_range(&self.a_query, range),
+            G1Query::BG1 => get_range(&self.b_g1_query, range),
+            G1Query::L => get_range(&self.l_query, range),
+            G1Query::H => get_range(&self.h_query, range),
+        }
+    }
+
+    fn b_g2_query(&self, range: Range<usize>) -> Result<Cow<'_, [P::G2Affine]>, Groth16Error> {
+        get_range(&self.b_g2_query, range)
+    }
+}
+
+/// The position of a query in a serialized [`ProvingKey`].
+#[derive(Debug, Clone, Copy)]
+struct QueryPosition {
+    offset: u64,
+    len: usize,
+    element_size: usize,
+}
+
+/// A [`ProvingKeySource`] reading the queries of a [`ProvingKey`] serialized with
+/// [`CanonicalSerialize::serialize_uncompressed`], e.g., from a [`File`](std::fs::File).
+///
+/// Only the verifying key, `beta_g1`, `delta_g1` and the positions of the queries are kept in memory. Every chunk is read
+/// from the reader and deserialized (with subgroup checks) when the prover requests it.
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> Result<(), taceo_groth16::Groth16Error> {
+/// use std::io::BufReader;
+/// use taceo_groth16::{G1Query, ProvingKeyReader, ProvingKeySource};
+///
+/// let file = BufReader::new(std::fs::File::open("circuit.pk")?);
+/// let source = ProvingKeyReader::<ark_bn254::Bn254, _>::new(file)?;
+/// println!("{} variables", source.g1_query_len(G1Query::A));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ProvingKeyReader<P: Pairing, R> {
+    reader: Mutex<R>,
+    vk: VerifyingKey<P>,
+    beta_g1: P::G1Affine,
+    delta_g1: P::G1Affine,
+    a_query: QueryPosition,
+    b_g1_query: QueryPosition,
+    b_g2_query: QueryPosition,
+    h_query: QueryPosition,
+    l_query: QueryPosition,
+}
+
+fn invalid_data(err: SerializationError) -> io::Error {
+    match err {
+        SerializationError::IoError(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+impl<P: Pairing, R: Read + Seek> ProvingKeyReader<P, R> {
+    /// Reads the verifying key and the positions of the queries of the proving key starting at the current position of
+    /// `reader`.
+    pub fn new(mut reader: R) -> Result<Self, Groth16Error> {
+        let vk = VerifyingKey::deserialize_uncompressed(&mut reader).map_err(invalid_data)?;
+        let beta_g1 = P::G1Affine::deserialize_uncompressed(&mut reader).map_err(invalid_data)?;
+        let delta_g1 = P::G1Affine::deserialize_uncompressed(&mut reader).map_err(invalid_data)?;
+        let g1_size = P::G1Affine::zero().uncompressed_size();
+        let g2_size = P::G2Affine::zero().uncompressed_size();
+        let a_query = Self::skip_query(&mut reader, g1_size)?;
+        let b_g1_query = Self::skip_query(&mut reader, g1_size)?;
+        let b_g2_query = Self::skip_query(&mut reader, g2_size)?;
+        let h_query = Self::skip_query(&mut reader, g1_size)?;
+        let l_query = Self::skip_query(&mut reader, g1_size)?;
+        // the queries are only read on demand, so a truncated key is detected here
+        let end = reader.stream_position()?;
+        if reader.seek(SeekFrom::End(0))? < end {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "serialized proving key is truncated",
+            )
+            .into());
+        }
+        Ok(Self {
+            reader: Mutex::new(reader),
+            vk,
+            beta_g1,
+            delta_g1,
+            a_query,
+            b_g1_query,
+            b_g2_query,
+            h_query,
+            l_query,
+        })
+    }
+
+    /// Reads the length of a serialized `Vec` and seeks to its end.
+    fn skip_query(reader: &mut R, element_size: usize) -> Result<QueryPosition, Groth16Error> {
+        let len = u64::deserialize_uncompressed(&mut *reader).map_err(invalid_data)?;
+        let offset = reader.stream_position()?;
+        let too_large = || io::Error::new(io::ErrorKind::InvalidData, "query length too large");
+        let size = len.checked_mul(element_size as u64).ok_or_else(too_large)?;
+        let len = usize::try_from(len).map_err(|_| too_large())?;
+        reader.seek(SeekFrom::Start(
+            offset.checked_add(size).ok_or_else(too_large)?,
+        ))?;
+        Ok(QueryPosition {
+            offset,
+            len,
+            element_size,
+        })
+    }
+
+    fn read<T: CanonicalDeserialize>(
+        &self,
+        query: QueryPosition,
+        range: Range<usize>,
+    ) -> Result<Vec<T>, Groth16Error> {
+        if range.start > range.end || range.end > query.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "range {range:?} out of bounds for query of length {}",
+                    query.len
+                ),
+            )
+            .into());
+        }
+        let mut bytes = vec![0; range.len() * query.element_size];
+        {
+            let mut reader = self.reader.lock().unwrap_or_else(PoisonError::into_inner);
+            reader.seek(SeekFrom::Start(
+                query.offset + (range.start * query.element_size) as u64,
+            ))?;
+            reader.read_exact(&mut bytes)?;
+        }
+        Ok(bytes
+            .chunks_exact(query.element_size)
+            .map(T::deserialize_uncompressed)
+            .collect::<Result<_, _>>()
+            .map_err(invalid_data)?)
+    }
+}
+
+impl<P: Pairing, R: Read + Seek + Send> ProvingKeySource<P> for ProvingKeyReader<P, R> {
+    fn verifying_key(&self) -> &VerifyingKey<P> {
+        &self.vk
+    }
+
+    fn beta_g1(&self) -> P::G1Affine {
+        self.beta_g1
+    }
+
+    fn delta_g1(&self) -> P::G1Affine {
+        self.delta_g1
+    }
+
+    fn g1_query_len(&self, query: G1Query) -> usize {
+        self.g1_query_position(query).len
+    }
+
+    fn b_g2_query_len(&self) -> usize {
+        self.b_g2_query.len
+    }
+
+    fn g1_query(
+        &self,
+        query: G1Query,
+        range: Range<usize>,
+    ) -> Result<Cow<'_, [P::G1Affine]>, Groth16Error> {
+        self.read(self.g1_query_position(query), range)
+            .map(Cow::Owned)
+    }
+
+    fn b_g2_query(&self, range: Range<usize>) -> Result<Cow<'_, [P::G2Affine]>, Groth16Error> {
+        self.read(self.b_g2_query, range).map(Cow::Owned)
+    }
+}
+
+impl<P: Pairing, R> ProvingKeyReader<P, R> {
+    fn g1_query_position(&self, query: G1Query) -> QueryPosition {
+        match query {
+            G1Query::A => self.a_query,
+            G1Query::BG1 => self.b_g1_query,
+            G1Query::L => self.l_query,
+            G1Query::H => self.h_query,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ark_bn254::Bn254;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::test_utils::TestCircuit;
+    use crate::{Groth16, LibSnarkReduction, ProverContext};
+
+    /// Copies every chunk out of the proving key, like a source reading from a file.
+    struct CopyingSource<'a> {
+        pk: &'a ProvingKey<Bn254>,
+        max_chunk: AtomicUsize,
+    }
+
+    impl ProvingKeySource<Bn254> for CopyingSource<'_> {
+        fn verifying_key(&self) -> &VerifyingKey<Bn254> {
+            &self.pk.vk
+        }
+
+        fn beta_g1(&self) -> ark_bn254::G1Affine {
+            self.pk.beta_g1
+        }
+
+        fn delta_g1(&self) -> ark_bn254::G1Affine {
+            self.pk.delta_g1
+        }
+
+        fn g1_query_len(&self, query: G1Query) -> usize {
+            self.pk.g1_query_len(query)
+        }
+
+        fn b_g2_query_len(&self) -> usize {
+            self.pk.b_g2_query_len()
+        }
+
+        fn g1_query(
+            &self,
+            query: G1Query,
+            range: Range<usize>,
+        ) -> Result<Cow<'_, [ark_bn254::G1Affine]>, Groth16Error> {
+            self.max_chunk.fetch_max(range.len(), Ordering::Relaxed);
+            Ok(Cow::Owned(self.pk.g1_query(query, range)?.into_owned()))
+        }
+
+        fn b_g2_query(
+            &self,
+            range: Range<usize>,
+        ) -> Result<Cow<'_, [ark_bn254::G2Affine]>, Groth16Error> {
+            self.max_chunk.fetch_max(range.len(), Ordering::Relaxed);
+            Ok(Cow::Owned(self.pk.b_g2_query(range)?.into_owned()))
+        }
+    }
+
+    #[test]
+    fn streaming_proof_matches_prove() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+        let source = CopyingSource {
+            pk: &circuit.pk,
+            max_chunk: AtomicUsize::new(0),
+        };
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::from_source(&source, &circuit.matrices)
+                .unwrap();
+
+        let is_proof = Groth16::prove_streaming(
+            &ctx,
+            &source,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+            NonZeroUsize::new(2).unwrap(),
+        )
+        .unwrap();
+        let should_proof = Groth16::prove_with_context(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        assert_eq!(is_proof, should_proof);
+        assert_eq!(source.max_chunk.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn mismatched_source_is_rejected() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        let h = Groth16::compute_h(&ctx, &circuit.matrices, &circuit.witness).unwrap();
+
+        let contributions = |pk: &ProvingKey<Bn254>, witness: &[ark_bn254::Fr]| {
+            Groth16::compute_contributions_streaming(pk, &h, witness, NonZeroUsize::MAX)
+        };
+        let num_variables = circuit.pk.g1_query_len(G1Query::A);
+
+        // more private variables than variables
+        let mut pk = circuit.pk.clone();
+        pk.l_query = vec![pk.l_query[0]; num_variables + 1];
+        assert!(matches!(
+            contributions(&pk, &circuit.witness),
+            Err(Groth16Error::ProvingKeyMismatch {
+                expected,
+                actual,
+            }) if expected == num_variables && actual == num_variables + 1
+        ));
+
+        // short B queries
+        let mut pk = circuit.pk.clone();
+        pk.b_g1_query.pop();
+        assert!(matches!(
+            contributions(&pk, &circuit.witness),
+            Err(Groth16Error::ProvingKeyMismatch {
+                expected,
+                actual,
+            }) if expected == num_variables && actual == num_variables - 1
+        ));
+        let mut pk = circuit.pk.clone();
+        pk.b_g2_query.pop();
+        assert!(matches!(
+            contributions(&pk, &circuit.witness),
+            Err(Groth16Error::ProvingKeyMismatch {
+                expected,
+                actual,
+            }) if expected == num_variables && actual == num_variables - 1
+        ));
+
+        // no variables at all
+        let mut pk = circuit.pk.clone();
+        pk.a_query.clear();
+        assert!(matches!(
+            contributions(&pk, &[]),
+            Err(Groth16Error::ProvingKeyMismatch {
+                expected: 0,
+                actual: 0,
+            })
+        ));
+    }
+
+    #[test]
+    fn reader_proof_matches_prove() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+        let mut bytes = Vec::new();
+        circuit.pk.serialize_uncompressed(&mut bytes).unwrap();
+        let source = ProvingKeyReader::<Bn254, _>::new(io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(source.verifying_key(), &circuit.pk.vk);
+        for query in [G1Query::A, G1Query::BG1, G1Query::L, G1Query::H] {
+            assert_eq!(source.g1_query_len(query), circuit.pk.g1_query_len(query));
+        }
+        assert_eq!(
+            source.b_g2_query(1..3).unwrap(),
+            &circuit.pk.b_g2_query[1..3]
+        );
+        assert!(matches!(
+            source.g1_query(G1Query::L, 0..circuit.pk.l_query.len() + 1),
+            Err(Groth16Error::KeySource(_))
+        ));
+
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        let is_proof = Groth16::prove_streaming(
+            &ctx,
+            &source,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+            NonZeroUsize::new(2).unwrap(),
+        )
+        .unwrap();
+        let should_proof = Groth16::prove_with_context(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        assert_eq!(is_proof, should_proof);
+
+        // the truncation is detected before proving
+        assert!(matches!(
+            ProvingKeyReader::<Bn254, _>::new(io::Cursor::new(&bytes[..bytes.len() - 1])),
+            Err(Groth16Error::KeySource(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn proving_key_rejects_out_of_bounds_range() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let len = circuit.pk.g1_query_len(G1Query::L);
+        assert!(matches!(
+            circuit.pk.g1_query(G1Query::L, 0..len + 1),
+            Err(Groth16Error::KeySource(_))
+        ));
+    }
+}
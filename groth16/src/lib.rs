@@ -1,34 +1,94 @@
-use ark_ec::VariableBaseMSM;
 use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{FftField, LegendreSymbol, PrimeField};
+use ark_ff::{FftField, Field, LegendreSymbol, PrimeField, UniformRand};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{CryptoRng, Rng};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::ops::Range;
 use tracing::instrument;
 
+use progress::checkpoint;
+
 pub use ark_groth16::{Proof, ProvingKey, VerifyingKey};
 pub use ark_relations::r1cs::ConstraintMatrices;
-pub use reduction::{CircomReduction, LibSnarkReduction, R1CSToQAP};
+pub use commitment::{
+    CommitmentProvingKey, CommitmentVerifyingKey, Commitments, GNARK_COMMITMENT_DST, HashToField,
+    ProofWithCommitments, expand_message_xmd_hash_to_field, gnark_hash_to_field,
+    sha256_hash_to_field,
+};
+pub use context::ProverContext;
+pub use key_source::{G1Query, ProvingKeyReader, ProvingKeySource};
+pub use msm::{ArkworksMsm, MsmBackend, PrecomputedMsm};
+pub use progress::{ProgressHook, ProvePhase};
+pub use reduction::{CircomDomain, CircomReduction, LibSnarkDomain, LibSnarkReduction, R1CSToQAP};
+pub use verifier::Groth16Verifier;
 
+#[cfg(feature = "aggregation")]
+pub mod aggregation;
+mod commitment;
+mod context;
+mod deterministic;
+mod key_source;
+mod msm;
+mod progress;
 mod reduction;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+mod verifier;
 
-macro_rules! rayon_join3 {
-    ($t1: expr, $t2: expr, $t3: expr) => {{
-        let ((x, y), z) = rayon::join(|| rayon::join($t1, $t2), $t3);
-        (x, y, z)
-    }};
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::join;
+
+/// Sequential fallback for `rayon::join` if the `parallel` feature is disabled.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn join<A, B, RA, RB>(oper_a: A, oper_b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA,
+    B: FnOnce() -> RB,
+{
+    (oper_a(), oper_b())
 }
 
 macro_rules! rayon_join5 {
     ($t1: expr, $t2: expr, $t3: expr, $t4: expr, $t5: expr) => {{
-        let ((((v, w), x), y), z) = rayon::join(
-            || rayon::join(|| rayon::join(|| rayon::join($t1, $t2), $t3), $t4),
+        let ((((v, w), x), y), z) = $crate::join(
+            || $crate::join(|| $crate::join(|| $crate::join($t1, $t2), $t3), $t4),
             $t5,
         );
         (v, w, x, y, z)
     }};
 }
-pub(crate) use rayon_join3;
+
+/// Returns a parallel iterator over a slice if the `parallel` feature is enabled, and a sequential one otherwise.
+macro_rules! cfg_iter {
+    ($e: expr) => {{
+        #[cfg(feature = "parallel")]
+        let result = $e.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let result = $e.iter();
+        result
+    }};
+}
+
+/// Returns a parallel mutable iterator over a slice if the `parallel` feature is enabled, and a sequential one otherwise.
+macro_rules! cfg_iter_mut {
+    ($e: expr) => {{
+        #[cfg(feature = "parallel")]
+        let result = $e.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let result = $e.iter_mut();
+        result
+    }};
+}
+pub(crate) use cfg_iter;
+pub(crate) use cfg_iter_mut;
+#[cfg(feature = "aggregation")]
+pub(crate) use rayon_join5;
 
 /// Computes the roots of unity over the provided prime field. This method
 /// is equivalent with [Circom's implementation](https://github.com/iden3/ffjavascript/blob/337b881579107ab74d5b2094dbe1910e33da4484/src/wasm_field1.js).
@@ -86,14 +146,154 @@ fn root_of_unity_for_groth16<F: PrimeField + FftField>(
     }
 }
 
+/// Errors that can occur during Groth16 proof generation and verification.
+#[derive(Debug, thiserror::Error)]
+pub enum Groth16Error {
+    /// The witness does not match the number of variables of the constraint system.
+    #[error("expected witness len {expected}, got len {actual}")]
+    InvalidWitnessLength {
+        /// The number of instance and witness variables of the constraint system.
+        expected: usize,
+        /// The length of the provided witness.
+        actual: usize,
+    },
+    /// The constraint system does not fit into an evaluation domain of the scalar field.
+    #[error("polynomial degree too large")]
+    PolynomialDegreeTooLarge,
+    /// The proving key does not match the constraint matrices.
+    #[error(
+        "proving key does not match constraint matrices: expected {expected} elements, got {actual}"
+    )]
+    ProvingKeyMismatch {
+        /// The number of elements expected from the constraint matrices.
+        expected: usize,
+        /// The number of elements in the proving key.
+        actual: usize,
+    },
+    /// The [`ProverContext`] was created for a different constraint system.
+    #[error("prover context does not match constraint matrices")]
+    ContextMismatch,
+    /// The number of public inputs does not match the verifying key.
+    #[error("expected {expected} public inputs, got {actual}")]
+    InvalidPublicInputLength {
+        /// The number of public inputs expected by the verifying key.
+        expected: usize,
+        /// The number of public inputs provided.
+        actual: usize,
+    },
+    /// The verifying key has no `gamma_abc_g1` element for the constant one.
+    #[error("malformed verifying key: gamma_abc_g1 is empty")]
+    MalformedVerifyingKey,
+    /// The proof could not be verified against the verifying key.
+    #[error("invalid proof")]
+    InvalidProof,
+    /// The witness does not satisfy the constraint with the given index.
+    #[error("witness does not satisfy constraint {0}")]
+    UnsatisfiedConstraint(usize),
+    /// The constraint matrices have no C matrix, so the constraints cannot be checked one by one.
+    #[error("constraint matrices without a C matrix cannot be checked")]
+    MissingCMatrix,
+    /// The witness does not satisfy the QAP of a constraint system without a C matrix, see [`ProveOptions::check_witness`].
+    #[error("witness does not satisfy the constraint system")]
+    UnsatisfiedWitness,
+    /// Proof generation was cancelled by the [`ProgressHook`] before the given phase.
+    #[error("proof generation cancelled before phase {0}")]
+    Cancelled(ProvePhase),
+    /// There are no proofs to aggregate.
+    #[error("cannot aggregate an empty set of proofs")]
+    EmptyAggregation,
+    /// The number of proofs does not match the number of public input sets.
+    #[error("expected public inputs for {expected} proofs, got {actual}")]
+    ProofCountMismatch {
+        /// The number of proofs.
+        expected: usize,
+        /// The number of public input sets.
+        actual: usize,
+    },
+    /// The structured reference string does not support aggregating the number of proofs.
+    #[error("SRS supports aggregating at most {max} proofs, got {actual}")]
+    SrsTooSmall {
+        /// The maximum number of proofs supported by the SRS.
+        max: usize,
+        /// The number of proofs to aggregate.
+        actual: usize,
+    },
+    /// An aggregate proof has more folding rounds than the number of aggregated proofs can represent.
+    #[error("aggregate proof has too many rounds: {0}")]
+    TooManyAggregationRounds(usize),
+    /// Reading from a [`ProvingKeySource`] failed.
+    #[error("failed to read proving key: {0}")]
+    KeySource(#[from] std::io::Error),
+    /// A Pedersen commitment or its proof of knowledge is invalid.
+    #[error("invalid commitment")]
+    InvalidCommitment,
+    /// The value of the commitment wire with the given index does not match the hash of the commitment.
+    #[error("commitment wire {0} does not match the commitment hash")]
+    CommitmentHashMismatch(usize),
+    /// The number of commitments does not match the commitment keys.
+    #[error("expected {expected} commitments, got {actual}")]
+    CommitmentCountMismatch {
+        /// The number of commitments expected by the keys.
+        expected: usize,
+        /// The number of commitments provided.
+        actual: usize,
+    },
+}
+
+/// The contributions of a witness to a Groth16 proof, computed by [`Groth16::compute_contributions`].
+///
+/// The contributions do not depend on the randomness `r` and `s` of the proof. They are combined
+/// into a proof with [`Groth16::combine`].
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofContributions<P: Pairing> {
+    /// The contribution to the proof element A: `alpha_g1 + sum_i w_i * a_query[i]`.
+    pub a: P::G1,
+    /// The contribution to the proof element B in G1: `beta_g1 + sum_i w_i * b_g1_query[i]`.
+    pub b_g1: P::G1,
+    /// The contribution to the proof element B in G2: `beta_g2 + sum_i w_i * b_g2_query[i]`.
+    pub b_g2: P::G2,
+    /// The contribution to the proof element C: `sum_i w_i * l_query[i] + sum_i h_i * h_query[i]`.
+    pub c: P::G1,
+}
+
+/// Additional checks and hooks used by [`Groth16::prove_with_options`].
+///
+/// All checks are disabled and no hook is set by default.
+#[derive(Clone, Copy, Default)]
+pub struct ProveOptions<'a> {
+    /// Checks that the witness satisfies the constraint system. See [`Groth16::check_witness`].
+    ///
+    /// Constraint matrices without a C matrix (e.g., loaded from a Circom `.zkey`) are checked through the QAP instead: the proof
+    /// is verified after computing it, which fails with [`Groth16Error::UnsatisfiedWitness`] if the evaluations of `h` do not
+    /// satisfy `A(x) * B(x) - C(x) = h(x) * t(x)`.
+    pub check_witness: bool,
+    /// Verifies the proof after computing it.
+    pub verify_after: bool,
+    /// A hook polled before every [`ProvePhase`] to report progress or cancel the proof.
+    pub progress: Option<&'a dyn ProgressHook>,
+}
+
+impl std::fmt::Debug for ProveOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProveOptions")
+            .field("check_witness", &self.check_witness)
+            .field("verify_after", &self.verify_after)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
 /// A Groth16 proof protocol.
 ///
-/// This struct should never be initialized, it only provides associated functions [`Groth16::prove`] and [`Groth16::verify`].
+/// This struct should never be initialized, it only provides associated functions [`Groth16::prove`], [`Groth16::verify`] and [`Groth16::rerandomize`].
 pub struct Groth16<P: Pairing> {
     phantom_data: PhantomData<P>,
 }
 
 impl<P: Pairing> Groth16<P> {
+    /// Creates a Groth16 proof for the provided witness using the randomness `r` and `s`.
+    ///
+    /// This recomputes the [`R1CSToQAP::Domain`] on every call. Use [`Groth16::prove_with_context`] to prove the same circuit many times.
     #[instrument(level = "debug", name = "Groth16 - Proof", skip_all)]
     pub fn prove<R: R1CSToQAP>(
         pkey: &ProvingKey<P>,
@@ -101,106 +301,478 @@ impl<P: Pairing> Groth16<P> {
         s: P::ScalarField,
         matrices: &ConstraintMatrices<P::ScalarField>,
         witness: &[P::ScalarField],
-    ) -> eyre::Result<Proof<P>> {
-        let witness_len = witness.len();
-        let witness_should_len = matrices.num_witness_variables + matrices.num_instance_variables;
-        if witness_len != witness_should_len {
-            eyre::bail!("expected witness len {witness_should_len}, got len {witness_len}",)
+    ) -> Result<Proof<P>, Groth16Error> {
+        let ctx = ProverContext::<P, R>::new(pkey, matrices)?;
+        Self::prove_with_context(&ctx, pkey, r, s, matrices, witness)
+    }
+
+    /// Creates a Groth16 proof for the provided witness using the randomness `r` and `s` and a precomputed [`ProverContext`].
+    ///
+    /// The context must have been created from the same proving key and constraint matrices.
+    #[instrument(level = "debug", name = "Groth16 - Proof with context", skip_all)]
+    pub fn prove_with_context<R: R1CSToQAP>(
+        ctx: &ProverContext<P, R>,
+        pkey: &ProvingKey<P>,
+        r: P::ScalarField,
+        s: P::ScalarField,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+    ) -> Result<Proof<P>, Groth16Error> {
+        Self::prove_with_options(ctx, pkey, r, s, matrices, witness, &ProveOptions::default())
+    }
+
+    /// Creates a Groth16 proof like [`Groth16::prove_with_context`], but performs the additional checks and calls the hook requested in [`ProveOptions`].
+    ///
+    /// Returns [`Groth16Error::UnsatisfiedConstraint`] or [`Groth16Error::UnsatisfiedWitness`] if the witness check fails, [`Groth16Error::InvalidProof`] if the proof cannot be verified
+    /// and [`Groth16Error::Cancelled`] if the [`ProgressHook`] cancelled proof generation.
+    #[instrument(level = "debug", name = "Groth16 - Proof with options", skip_all)]
+    pub fn prove_with_options<R: R1CSToQAP>(
+        ctx: &ProverContext<P, R>,
+        pkey: &ProvingKey<P>,
+        r: P::ScalarField,
+        s: P::ScalarField,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+        options: &ProveOptions,
+    ) -> Result<Proof<P>, Groth16Error> {
+        let hook = options.progress.unwrap_or(&());
+        // without a C matrix, the witness is checked through the QAP by verifying the proof
+        let mut check_qap = false;
+        if options.check_witness {
+            checkpoint(hook, ProvePhase::CheckWitness)?;
+            match Self::check_witness(matrices, witness) {
+                Err(Groth16Error::MissingCMatrix) => check_qap = true,
+                result => result?,
+            }
         }
-        let h = R::witness_map_from_matrices::<P>(matrices, witness)?;
-        let proof = Self::create_proof_with_assignment(
+        let h = Self::compute_h_with_hook(ctx, matrices, witness, hook)?;
+        checkpoint(hook, ProvePhase::Msm)?;
+        let contributions = Self::compute_contributions(pkey, &h, witness)?;
+        checkpoint(hook, ProvePhase::Combine)?;
+        let proof = Self::combine(pkey, &contributions, r, s);
+        if options.verify_after || check_qap {
+            checkpoint(hook, ProvePhase::Verify)?;
+            let public_inputs = &witness[1..matrices.num_instance_variables];
+            Self::verify(&pkey.vk, &proof, public_inputs).map_err(|err| match err {
+                Groth16Error::InvalidProof if check_qap => Groth16Error::UnsatisfiedWitness,
+                err => err,
+            })?;
+        }
+        Ok(proof)
+    }
+
+    /// Derives the randomness `r` and `s` of a proof deterministically from a secret `seed` and the witness.
+    ///
+    /// Similar to RFC 6979, `r` and `s` are computed as SHA-512 hashes of a domain separation tag, the `seed` and a hash of the
+    /// whole witness (including the public inputs), reduced modulo the order of the scalar field. The same seed and witness always
+    /// yield the same randomness.
+    pub fn derive_randomness(
+        seed: &[u8; 32],
+        witness: &[P::ScalarField],
+    ) -> (P::ScalarField, P::ScalarField) {
+        deterministic::derive_randomness(seed, witness)
+    }
+
+    /// Creates a Groth16 proof like [`Groth16::prove_with_context`], but derives `r` and `s` with [`Groth16::derive_randomness`]
+    /// instead of taking them from the caller.
+    ///
+    /// Proving the same witness with the same seed yields the same proof, which is useful for reproducible test vectors and audits.
+    /// The zero-knowledge property of the proof relies on the secrecy of the `seed`, as `r` and `s` are the only randomness of a proof.
+    /// Never reuse a seed that is known to others and use randomly sampled `r` and `s` (e.g., [`Groth16::prove_batch`]) unless
+    /// deterministic proofs are required.
+    #[instrument(level = "debug", name = "Groth16 - Proof deterministic", skip_all)]
+    pub fn prove_deterministic<R: R1CSToQAP>(
+        ctx: &ProverContext<P, R>,
+        pkey: &ProvingKey<P>,
+        seed: &[u8; 32],
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+    ) -> Result<Proof<P>, Groth16Error> {
+        let (r, s) = Self::derive_randomness(seed, witness);
+        Self::prove_with_context(ctx, pkey, r, s, matrices, witness)
+    }
+
+    /// Creates a Groth16 proof like [`Groth16::prove_with_context`], but reads the proving key from a [`ProvingKeySource`].
+    ///
+    /// The MSMs read the queries of the proving key in chunks of at most `chunk_size` elements, so the memory used for the proving key
+    /// is bounded by the chunk size instead of the size of the key. See [`Groth16::compute_contributions_streaming`].
+    #[instrument(level = "debug", name = "Groth16 - Proof streaming", skip_all)]
+    pub fn prove_streaming<R: R1CSToQAP, S: ProvingKeySource<P>>(
+        ctx: &ProverContext<P, R>,
+        source: &S,
+        r: P::ScalarField,
+        s: P::ScalarField,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+        chunk_size: NonZeroUsize,
+    ) -> Result<Proof<P>, Groth16Error> {
+        let h = Self::compute_h(ctx, matrices, witness)?;
+        let contributions = Self::compute_contributions_streaming(source, &h, witness, chunk_size)?;
+        Ok(Self::combine_with_source(source, &contributions, r, s))
+    }
+
+    /// Creates a Groth16 proof like [`Groth16::prove_with_context`], but computes the MSMs with the given [`MsmBackend`].
+    ///
+    /// Use a [`PrecomputedMsm`] built once from `pkey` to trade memory for proving speed.
+    #[instrument(level = "debug", name = "Groth16 - Proof with backend", skip_all)]
+    pub fn prove_with_backend<R: R1CSToQAP, M: MsmBackend<P>>(
+        ctx: &ProverContext<P, R>,
+        pkey: &ProvingKey<P>,
+        backend: &M,
+        r: P::ScalarField,
+        s: P::ScalarField,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+    ) -> Result<Proof<P>, Groth16Error> {
+        let h = Self::compute_h(ctx, matrices, witness)?;
+        let contributions = Self::compute_contributions_with_backend(
             pkey,
-            r,
-            s,
-            h,
+            backend,
+            &h,
             witness,
-            matrices.num_instance_variables,
+            NonZeroUsize::MAX,
         )?;
-        Ok(proof)
+        Ok(Self::combine(pkey, &contributions, r, s))
+    }
+
+    /// Checks that the witness satisfies every constraint `<A_i, w> * <B_i, w> = <C_i, w>` of the constraint system.
+    ///
+    /// Returns [`Groth16Error::UnsatisfiedConstraint`] with the index of the first unsatisfied constraint.
+    ///
+    /// Constraint matrices without a C matrix (e.g., loaded from a Circom `.zkey`) cannot be checked constraint by constraint,
+    /// as the C matrix is only part of the proving key in that case. After checking the length of the witness, returns
+    /// [`Groth16Error::MissingCMatrix`] for them. [`ProveOptions::check_witness`] checks such witnesses through the QAP.
+    #[instrument(level = "debug", name = "check witness", skip_all)]
+    pub fn check_witness(
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+    ) -> Result<(), Groth16Error> {
+        let witness_should_len = matrices.num_witness_variables + matrices.num_instance_variables;
+        if witness.len() != witness_should_len {
+            return Err(Groth16Error::InvalidWitnessLength {
+                expected: witness_should_len,
+                actual: witness.len(),
+            });
+        }
+        if matrices.c.len() < matrices.num_constraints {
+            return Err(Groth16Error::MissingCMatrix);
+        }
+        let evaluate = |row: &[(P::ScalarField, usize)]| {
+            row.iter()
+                .map(|(coeff, index)| *coeff * witness[*index])
+                .sum::<P::ScalarField>()
+        };
+        let is_unsatisfied = |i: &usize| {
+            evaluate(&matrices.a[*i]) * evaluate(&matrices.b[*i]) != evaluate(&matrices.c[*i])
+        };
+        #[cfg(feature = "parallel")]
+        let unsatisfied = (0..matrices.num_constraints)
+            .into_par_iter()
+            .find_first(is_unsatisfied);
+        #[cfg(not(feature = "parallel"))]
+        let unsatisfied = (0..matrices.num_constraints).find(is_unsatisfied);
+        match unsatisfied {
+            Some(i) => Err(Groth16Error::UnsatisfiedConstraint(i)),
+            None => Ok(()),
+        }
+    }
+
+    /// Creates Groth16 proofs for many witnesses of the same circuit.
+    ///
+    /// The [`ProverContext`] is computed once and, with the `parallel` feature, the proofs are generated in parallel, so that the
+    /// witness maps and MSMs of different proofs are scheduled on all available cores. The randomness
+    /// `r` and `s` for every proof is sampled from `rng` upfront.
+    ///
+    /// Returns an error if no [`ProverContext`] can be created, otherwise one result per witness (in the same order).
+    #[instrument(level = "debug", name = "Groth16 - Batch Proof", skip_all, fields(batch_size = witnesses.len()))]
+    pub fn prove_batch<R: R1CSToQAP, W: AsRef<[P::ScalarField]> + Sync, Rn: Rng + CryptoRng>(
+        pkey: &ProvingKey<P>,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witnesses: &[W],
+        rng: &mut Rn,
+    ) -> Result<Vec<Result<Proof<P>, Groth16Error>>, Groth16Error> {
+        let ctx = ProverContext::<P, R>::new(pkey, matrices)?;
+        Ok(Self::prove_batch_with_context(
+            &ctx, pkey, matrices, witnesses, rng,
+        ))
     }
 
-    fn calculate_coeff<C>(
-        initial: C,
-        query: &[C::Affine],
+    /// Creates Groth16 proofs for many witnesses of the same circuit using a precomputed [`ProverContext`].
+    ///
+    /// See [`Groth16::prove_batch`] for details. Returns one result per witness (in the same order).
+    #[instrument(level = "debug", name = "Groth16 - Batch Proof with context", skip_all, fields(batch_size = witnesses.len()))]
+    pub fn prove_batch_with_context<
+        R: R1CSToQAP,
+        W: AsRef<[P::ScalarField]> + Sync,
+        Rn: Rng + CryptoRng,
+    >(
+        ctx: &ProverContext<P, R>,
+        pkey: &ProvingKey<P>,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witnesses: &[W],
+        rng: &mut Rn,
+    ) -> Vec<Result<Proof<P>, Groth16Error>> {
+        let randomness = witnesses
+            .iter()
+            .map(|_| (P::ScalarField::rand(rng), P::ScalarField::rand(rng)))
+            .collect::<Vec<_>>();
+        #[cfg(feature = "parallel")]
+        let iter = (witnesses, randomness).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = witnesses.iter().zip(randomness);
+        iter.map(|(witness, (r, s))| {
+            Self::prove_with_context(ctx, pkey, r, s, matrices, witness.as_ref())
+        })
+        .collect()
+    }
+
+    /// Computes the QAP witness `h` (the first phase of [`Groth16::prove`]).
+    ///
+    /// Together with [`Groth16::compute_contributions`] and [`Groth16::combine`] this allows to run
+    /// the phases of proof generation separately, e.g., on different machines.
+    #[instrument(level = "debug", name = "compute h", skip_all)]
+    pub fn compute_h<R: R1CSToQAP>(
+        ctx: &ProverContext<P, R>,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+    ) -> Result<Vec<P::ScalarField>, Groth16Error> {
+        Self::compute_h_with_hook(ctx, matrices, witness, &())
+    }
+
+    fn compute_h_with_hook<R: R1CSToQAP>(
+        ctx: &ProverContext<P, R>,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+        hook: &dyn ProgressHook,
+    ) -> Result<Vec<P::ScalarField>, Groth16Error> {
+        ctx.check_matrices(matrices)?;
+        let witness_len = witness.len();
+        let witness_should_len = matrices.num_witness_variables + matrices.num_instance_variables;
+        if witness_len != witness_should_len {
+            return Err(Groth16Error::InvalidWitnessLength {
+                expected: witness_should_len,
+                actual: witness_len,
+            });
+        }
+        R::witness_map_with_domain::<P>(ctx.domain(), matrices, witness, hook)
+    }
+
+    /// Computes the MSM of `scalars` with the bases starting at `offset`, fetching at most `chunk_size` bases at once.
+    ///
+    /// `msm` computes the MSM of a chunk given the offset of its first base.
+    fn msm_chunked<'a, C: CurveGroup<ScalarField = P::ScalarField>>(
+        offset: usize,
+        scalars: &[P::ScalarField],
+        chunk_size: NonZeroUsize,
+        fetch: impl Fn(Range<usize>) -> Result<Cow<'a, [C::Affine]>, Groth16Error>,
+        msm: impl Fn(usize, &[C::Affine], &[P::ScalarField]) -> Result<C, Groth16Error>,
+    ) -> Result<C, Groth16Error> {
+        let mut acc = C::zero();
+        let mut start = offset;
+        for scalars in scalars.chunks(chunk_size.get()) {
+            let bases = fetch(start..start + scalars.len())?;
+            acc += msm(start, &bases, scalars)?;
+            start += scalars.len();
+        }
+        Ok(acc)
+    }
+
+    fn calculate_coeff<'a, C>(
         vk_param: C::Affine,
         witness: &[P::ScalarField],
-    ) -> C
+        chunk_size: NonZeroUsize,
+        fetch: impl Fn(Range<usize>) -> Result<Cow<'a, [C::Affine]>, Groth16Error>,
+        msm: impl Fn(usize, &[C::Affine], &[P::ScalarField]) -> Result<C, Groth16Error>,
+    ) -> Result<C, Groth16Error>
     where
         C: CurveGroup<ScalarField = P::ScalarField>,
     {
-        let acc = C::msm_unchecked(&query[1..], witness);
-        let mut res = initial;
-        res += query[0].into_group();
+        let acc = Self::msm_chunked::<C>(1, witness, chunk_size, &fetch, msm)?;
+        let mut res = fetch(0..1)?[0].into_group();
         res += vk_param.into_group();
         res += acc;
-        res
+        Ok(res)
     }
 
-    #[instrument(level = "debug", name = "create proof with assignment", skip_all)]
-    fn create_proof_with_assignment(
+    /// Computes the MSMs of the witness and the QAP witness `h` with the proving key (the second phase of [`Groth16::prove`]).
+    ///
+    /// The result does not depend on the randomness `r` and `s` and is combined into a proof with [`Groth16::combine`].
+    #[instrument(level = "debug", name = "compute contributions", skip_all)]
+    pub fn compute_contributions(
         pkey: &ProvingKey<P>,
-        r: P::ScalarField,
-        s: P::ScalarField,
-        h: Vec<P::ScalarField>,
+        h: &[P::ScalarField],
+        witness: &[P::ScalarField],
+    ) -> Result<ProofContributions<P>, Groth16Error> {
+        // a single chunk borrows the whole query, so this is a plain MSM per query
+        Self::compute_contributions_streaming(pkey, h, witness, NonZeroUsize::MAX)
+    }
+
+    /// Computes the MSMs like [`Groth16::compute_contributions`], but reads the queries of the proving key from a [`ProvingKeySource`]
+    /// in chunks of at most `chunk_size` elements.
+    ///
+    /// The MSMs of the five queries run in parallel, so at most five chunks are held in memory at the same time.
+    #[instrument(level = "debug", name = "compute contributions streaming", skip_all)]
+    pub fn compute_contributions_streaming<S: ProvingKeySource<P>>(
+        source: &S,
+        h: &[P::ScalarField],
         witness: &[P::ScalarField],
-        num_inputs: usize,
-    ) -> eyre::Result<Proof<P>> {
-        let delta_g1 = pkey.delta_g1.into_group();
-        let alpha_g1 = pkey.vk.alpha_g1;
-        let beta_g1 = pkey.beta_g1;
-        let beta_g2 = pkey.vk.beta_g2;
-        let delta_g2 = pkey.vk.delta_g2.into_group();
-
-        let (r_g1, s_g1, s_g2, l_acc, h_acc) = rayon_join5!(
+        chunk_size: NonZeroUsize,
+    ) -> Result<ProofContributions<P>, Groth16Error> {
+        Self::compute_contributions_with_backend(source, &ArkworksMsm, h, witness, chunk_size)
+    }
+
+    /// Computes the MSMs like [`Groth16::compute_contributions_streaming`], but with the given [`MsmBackend`] instead of
+    /// [`ArkworksMsm`].
+    #[instrument(level = "debug", name = "compute contributions with backend", skip_all)]
+    pub fn compute_contributions_with_backend<S: ProvingKeySource<P>, M: MsmBackend<P>>(
+        source: &S,
+        backend: &M,
+        h: &[P::ScalarField],
+        witness: &[P::ScalarField],
+        chunk_size: NonZeroUsize,
+    ) -> Result<ProofContributions<P>, Groth16Error> {
+        let num_variables = source.g1_query_len(G1Query::A);
+        // every witness starts with the constant one
+        if num_variables == 0 {
+            return Err(Groth16Error::ProvingKeyMismatch {
+                expected: witness.len(),
+                actual: 0,
+            });
+        }
+        if witness.len() != num_variables {
+            return Err(Groth16Error::InvalidWitnessLength {
+                expected: num_variables,
+                actual: witness.len(),
+            });
+        }
+        for len in [source.g1_query_len(G1Query::BG1), source.b_g2_query_len()] {
+            if len != num_variables {
+                return Err(Groth16Error::ProvingKeyMismatch {
+                    expected: num_variables,
+                    actual: len,
+                });
+            }
+        }
+        let num_private = source.g1_query_len(G1Query::L);
+        let num_inputs =
+            num_variables
+                .checked_sub(num_private)
+                .ok_or(Groth16Error::ProvingKeyMismatch {
+                    expected: num_variables,
+                    actual: num_private,
+                })?;
+        let h = &h[..h.len().min(source.g1_query_len(G1Query::H))];
+        let vk = source.verifying_key();
+        let alpha_g1 = vk.alpha_g1;
+        let beta_g1 = source.beta_g1();
+        let beta_g2 = vk.beta_g2;
+
+        let (a, b_g1, b_g2, l_acc, h_acc) = rayon_join5!(
             || {
                 let compute_a =
-                    tracing::debug_span!("compute A in create proof with assignment").entered();
+                    tracing::debug_span!("compute A in compute contributions").entered();
                 // Compute A
-                let r_g1 = delta_g1 * r;
-                let r_g1 = Self::calculate_coeff(r_g1, &pkey.a_query, alpha_g1, &witness[1..]);
+                let a = Self::calculate_coeff(
+                    alpha_g1,
+                    &witness[1..],
+                    chunk_size,
+                    |range| source.g1_query(G1Query::A, range),
+                    |offset, bases, scalars| backend.msm_g1(G1Query::A, offset, bases, scalars),
+                );
                 compute_a.exit();
-                r_g1
+                a
             },
             || {
                 let compute_b =
-                    tracing::debug_span!("compute B/G1 in create proof with assignment").entered();
+                    tracing::debug_span!("compute B/G1 in compute contributions").entered();
                 // Compute B in G1
                 // In original implementation this is skipped if r==0, however r is shared in our case
-                let s_g1 = delta_g1 * s;
-                let s_g1 = Self::calculate_coeff(s_g1, &pkey.b_g1_query, beta_g1, &witness[1..]);
+                let b_g1 = Self::calculate_coeff(
+                    beta_g1,
+                    &witness[1..],
+                    chunk_size,
+                    |range| source.g1_query(G1Query::BG1, range),
+                    |offset, bases, scalars| backend.msm_g1(G1Query::BG1, offset, bases, scalars),
+                );
                 compute_b.exit();
-                s_g1
+                b_g1
             },
             || {
                 let compute_b =
-                    tracing::debug_span!("compute B/G2 in create proof with assignment").entered();
+                    tracing::debug_span!("compute B/G2 in compute contributions").entered();
                 // Compute B in G2
-                let s_g2 = delta_g2 * s;
-                let s_g2 = Self::calculate_coeff(s_g2, &pkey.b_g2_query, beta_g2, &witness[1..]);
+                let b_g2 = Self::calculate_coeff(
+                    beta_g2,
+                    &witness[1..],
+                    chunk_size,
+                    |range| source.b_g2_query(range),
+                    |offset, bases, scalars| backend.msm_g2(offset, bases, scalars),
+                );
                 compute_b.exit();
-                s_g2
+                b_g2
             },
             || {
                 let msm_l_query = tracing::debug_span!("msm l_query").entered();
-                let result = P::G1::msm_unchecked(&pkey.l_query, &witness[num_inputs..]);
+                let result = Self::msm_chunked::<P::G1>(
+                    0,
+                    &witness[num_inputs..],
+                    chunk_size,
+                    |range| source.g1_query(G1Query::L, range),
+                    |offset, bases, scalars| backend.msm_g1(G1Query::L, offset, bases, scalars),
+                );
                 msm_l_query.exit();
                 result
             },
             || {
                 let msm_h_query = tracing::debug_span!("msm h_query").entered();
                 //perform the msm for h
-                let result = P::G1::msm_unchecked(&pkey.h_query, &h);
+                let result = Self::msm_chunked::<P::G1>(
+                    0,
+                    h,
+                    chunk_size,
+                    |range| source.g1_query(G1Query::H, range),
+                    |offset, bases, scalars| backend.msm_g1(G1Query::H, offset, bases, scalars),
+                );
                 msm_h_query.exit();
                 result
             }
         );
 
+        Ok(ProofContributions {
+            a: a?,
+            b_g1: b_g1?,
+            b_g2: b_g2?,
+            c: l_acc? + h_acc?,
+        })
+    }
+
+    /// Combines the [`ProofContributions`] with the randomness `r` and `s` into a proof (the last phase of [`Groth16::prove`]).
+    #[instrument(level = "debug", name = "combine", skip_all)]
+    pub fn combine(
+        pkey: &ProvingKey<P>,
+        contributions: &ProofContributions<P>,
+        r: P::ScalarField,
+        s: P::ScalarField,
+    ) -> Proof<P> {
+        Self::combine_with_source(pkey, contributions, r, s)
+    }
+
+    fn combine_with_source<S: ProvingKeySource<P>>(
+        source: &S,
+        contributions: &ProofContributions<P>,
+        r: P::ScalarField,
+        s: P::ScalarField,
+    ) -> Proof<P> {
+        let delta_g1 = source.delta_g1().into_group();
+        let delta_g2 = source.verifying_key().delta_g2.into_group();
+
+        let g_a = contributions.a + delta_g1 * r;
+        let g1_b = contributions.b_g1 + delta_g1 * s;
+        let g2_b = contributions.b_g2 + delta_g2 * s;
+
         let rs = r * s;
         let r_s_delta_g1 = delta_g1 * rs;
 
-        let g_a = r_g1;
-        let g1_b = s_g1;
-
         let r_g1_b = g1_b * r;
 
         let s_g_a = g_a * s;
@@ -208,35 +780,443 @@ impl<P: Pairing> Groth16<P> {
         let mut g_c = s_g_a;
         g_c += r_g1_b;
         g_c -= r_s_delta_g1;
-        g_c += l_acc;
-
-        g_c += h_acc;
-
-        let g2_b = s_g2;
+        g_c += contributions.c;
 
-        Ok(Proof {
+        Proof {
             a: g_a.into_affine(),
             b: g2_b.into_affine(),
             c: g_c.into_affine(),
-        })
+        }
     }
 }
 
 impl<P: Pairing> Groth16<P> {
     /// Verify a Groth16 proof.
-    /// This method is a wrapper arkworks Groth16 and does not use MPC.
+    /// This method prepares the verifying key on every call. Use a [`Groth16Verifier`] to verify many proofs under the same key.
     pub fn verify(
         vk: &VerifyingKey<P>,
         proof: &Proof<P>,
         public_inputs: &[P::ScalarField],
-    ) -> eyre::Result<()> {
-        let vk = ark_groth16::prepare_verifying_key(vk);
-        let proof_valid = ark_groth16::Groth16::<P>::verify_proof(&vk, proof, public_inputs)
-            .map_err(eyre::Report::from)?;
-        if proof_valid {
+    ) -> Result<(), Groth16Error> {
+        Groth16Verifier::new(vk)?.verify(proof, public_inputs)
+    }
+
+    /// Re-randomizes a Groth16 proof without knowledge of the witness.
+    ///
+    /// The resulting proof verifies for the same public inputs, but is unlinkable to the original
+    /// proof. See Figure 1 in <https://eprint.iacr.org/2020/811.pdf>:
+    /// ```text
+    /// A' = (1/r1) * A
+    /// B' = r1 * B + r1 * r2 * delta_g2
+    /// C' = C + r2 * A
+    /// ```
+    pub fn rerandomize<R: Rng + CryptoRng>(
+        vk: &VerifyingKey<P>,
+        proof: &Proof<P>,
+        rng: &mut R,
+    ) -> Proof<P> {
+        let (r1, r1_inv) = loop {
+            let r1 = P::ScalarField::rand(rng);
+            if let Some(r1_inv) = r1.inverse() {
+                break (r1, r1_inv);
+            }
+        };
+        let r2 = P::ScalarField::rand(rng);
+
+        let a = proof.a * r1_inv;
+        let b = proof.b * r1 + vk.delta_g2 * (r1 * r2);
+        let c = proof.c.into_group() + proof.a * r2;
+
+        Proof {
+            a: a.into_affine(),
+            b: b.into_affine(),
+            c: c.into_affine(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::test_utils::TestCircuit;
+
+    #[test]
+    fn rerandomized_proof_verifies() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let proof = circuit.prove(&mut rng);
+        let vk = &circuit.pk.vk;
+
+        let rerandomized = Groth16::rerandomize(vk, &proof, &mut rng);
+        assert_ne!(proof.a, rerandomized.a);
+        assert_ne!(proof.b, rerandomized.b);
+        assert_ne!(proof.c, rerandomized.c);
+        Groth16::verify(vk, &rerandomized, circuit.public_inputs()).unwrap();
+
+        let twice = Groth16::rerandomize(vk, &rerandomized, &mut rng);
+        assert_ne!(rerandomized, twice);
+        Groth16::verify(vk, &twice, circuit.public_inputs()).unwrap();
+    }
+
+    #[test]
+    fn batch_proofs_verify() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let mut witnesses = vec![circuit.witness.clone(); 4];
+        witnesses[2].pop();
+
+        let proofs = Groth16::prove_batch::<LibSnarkReduction, _, _>(
+            &circuit.pk,
+            &circuit.matrices,
+            &witnesses,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(proofs.len(), witnesses.len());
+        for (idx, proof) in proofs.into_iter().enumerate() {
+            if idx == 2 {
+                assert!(matches!(
+                    proof,
+                    Err(Groth16Error::InvalidWitnessLength { .. })
+                ));
+            } else {
+                Groth16::verify(&circuit.pk.vk, &proof.unwrap(), circuit.public_inputs()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn split_proving_matches_prove() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+
+        let h = Groth16::compute_h(&ctx, &circuit.matrices, &circuit.witness).unwrap();
+        let contributions =
+            Groth16::<Bn254>::compute_contributions(&circuit.pk, &h, &circuit.witness).unwrap();
+
+        // ship the contributions to another party
+        let mut bytes = Vec::new();
+        contributions.serialize_compressed(&mut bytes).unwrap();
+        let contributions =
+            ProofContributions::<Bn254>::deserialize_compressed(bytes.as_slice()).unwrap();
+
+        let is_proof = Groth16::combine(&circuit.pk, &contributions, r, s);
+        let should_proof = Groth16::prove::<LibSnarkReduction>(
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        assert_eq!(is_proof, should_proof);
+        Groth16::verify(&circuit.pk.vk, &is_proof, circuit.public_inputs()).unwrap();
+
+        assert!(matches!(
+            Groth16::<Bn254>::compute_contributions(&circuit.pk, &h, &circuit.witness[1..]),
+            Err(Groth16Error::InvalidWitnessLength { .. })
+        ));
+    }
+
+    #[test]
+    fn prove_with_options_reports_wrong_witness() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        let options = ProveOptions {
+            check_witness: true,
+            verify_after: true,
+            ..Default::default()
+        };
+        Groth16::prove_with_options(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+            &options,
+        )
+        .unwrap();
+
+        // the witness is [1, c, a, b, a^2, b^2] and a^2 is only used in the second constraint
+        let mut witness = circuit.witness.clone();
+        witness[4] += ark_bn254::Fr::from(1);
+        assert!(matches!(
+            Groth16::prove_with_options(
+                &ctx,
+                &circuit.pk,
+                r,
+                s,
+                &circuit.matrices,
+                &witness,
+                &options,
+            ),
+            Err(Groth16Error::UnsatisfiedConstraint(1))
+        ));
+        let options = ProveOptions {
+            verify_after: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Groth16::prove_with_options(
+                &ctx,
+                &circuit.pk,
+                r,
+                s,
+                &circuit.matrices,
+                &witness,
+                &options,
+            ),
+            Err(Groth16Error::InvalidProof)
+        ));
+        // without checks the invalid proof is returned
+        Groth16::prove_with_options(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &witness,
+            &ProveOptions::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn progress_hook_reports_phases_and_cancels() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+
+        let phases = std::sync::Mutex::new(Vec::new());
+        let record = |phase| {
+            phases.lock().unwrap().push(phase);
+            std::ops::ControlFlow::Continue(())
+        };
+        let options = ProveOptions {
+            check_witness: true,
+            verify_after: true,
+            progress: Some(&record),
+        };
+        let proof = Groth16::prove_with_options(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            phases.into_inner().unwrap(),
+            [
+                ProvePhase::CheckWitness,
+                ProvePhase::EvaluateConstraints,
+                ProvePhase::Fft,
+                ProvePhase::Msm,
+                ProvePhase::Combine,
+                ProvePhase::Verify,
+            ]
+        );
+        assert_eq!(
+            proof,
+            Groth16::prove_with_context(
+                &ctx,
+                &circuit.pk,
+                r,
+                s,
+                &circuit.matrices,
+                &circuit.witness
+            )
+            .unwrap()
+        );
+
+        let cancel_at_msm = |phase| {
+            if phase == ProvePhase::Msm {
+                std::ops::ControlFlow::Break(())
+            } else {
+                std::ops::ControlFlow::Continue(())
+            }
+        };
+        let options = ProveOptions {
+            progress: Some(&cancel_at_msm),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Groth16::prove_with_options(
+                &ctx,
+                &circuit.pk,
+                r,
+                s,
+                &circuit.matrices,
+                &circuit.witness,
+                &options,
+            ),
+            Err(Groth16Error::Cancelled(ProvePhase::Msm))
+        ));
+    }
+
+    #[test]
+    fn deterministic_proofs_are_reproducible() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        let prove = |seed: &[u8; 32], witness: &[ark_bn254::Fr]| {
+            Groth16::prove_deterministic(&ctx, &circuit.pk, seed, &circuit.matrices, witness)
+                .unwrap()
+        };
+
+        let proof = prove(&[1; 32], &circuit.witness);
+        Groth16::verify(&circuit.pk.vk, &proof, circuit.public_inputs()).unwrap();
+        assert_eq!(proof, prove(&[1; 32], &circuit.witness));
+        assert_ne!(proof, prove(&[2; 32], &circuit.witness));
+
+        let (r, s) = Groth16::<Bn254>::derive_randomness(&[1; 32], &circuit.witness);
+        assert_ne!(r, s);
+        let mut witness = circuit.witness.clone();
+        witness[2] += ark_bn254::Fr::from(1);
+        assert_ne!(
+            (r, s),
+            Groth16::<Bn254>::derive_randomness(&[1; 32], &witness)
+        );
+    }
+
+    #[test]
+    fn rerandomized_proof_keeps_public_inputs_bound() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let proof = circuit.prove(&mut rng);
+        let vk = &circuit.pk.vk;
+
+        let rerandomized = Groth16::rerandomize(vk, &proof, &mut rng);
+        let wrong_inputs = [circuit.public_inputs()[0] + ark_bn254::Fr::from(1)];
+        assert!(matches!(
+            Groth16::verify(vk, &rerandomized, &wrong_inputs),
+            Err(Groth16Error::InvalidProof)
+        ));
+    }
+
+    fn check_roots_of_unity<F: PrimeField + FftField>() {
+        let (q, _) = roots_of_unity::<F>();
+        for log_size in 1..=F::TWO_ADICITY {
+            let mut domain = GeneralEvaluationDomain::<F>::new(1 << log_size).unwrap();
+            let root = root_of_unity_for_groth16(log_size as usize, &mut domain);
+            assert_eq!(domain.group_gen().pow([1 << log_size]), F::one());
+            assert_ne!(domain.group_gen().pow([1 << (log_size - 1)]), F::one());
+            if log_size == F::TWO_ADICITY {
+                // ffjavascript special-cases the largest domain
+                assert_eq!(root, q.square());
+            } else {
+                assert_eq!(root.square(), domain.group_gen());
+                assert_eq!(root.pow([1 << log_size]), -F::one());
+            }
+        }
+    }
+
+    #[test]
+    fn circom_roots_of_unity_for_all_domain_sizes() {
+        check_roots_of_unity::<ark_bn254::Fr>();
+        check_roots_of_unity::<ark_bls12_381::Fr>();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use ark_ec::pairing::Pairing;
+    use ark_ff::UniformRand;
+    use ark_relations::{
+        lc,
+        r1cs::{
+            ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+            OptimizationGoal, SynthesisError,
+        },
+    };
+    use ark_snark::SNARK;
+    use ark_std::rand::{CryptoRng, RngCore};
+
+    use crate::{Groth16, LibSnarkReduction, Proof, ProvingKey};
+
+    /// Proves knowledge of `a` and `b` with `a * b = c`, where `c` is the only public input.
+    /// Additionally constrains `a^2` and `b^2` so that the domain is larger than trivial.
+    #[derive(Clone, Copy)]
+    struct MulCircuit<F> {
+        a: F,
+        b: F,
+    }
+
+    impl<F: ark_ff::PrimeField> ConstraintSynthesizer<F> for MulCircuit<F> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+            let c = cs.new_input_variable(|| Ok(self.a * self.b))?;
+            let a = cs.new_witness_variable(|| Ok(self.a))?;
+            let b = cs.new_witness_variable(|| Ok(self.b))?;
+            let a2 = cs.new_witness_variable(|| Ok(self.a.square()))?;
+            let b2 = cs.new_witness_variable(|| Ok(self.b.square()))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+            cs.enforce_constraint(lc!() + a, lc!() + a, lc!() + a2)?;
+            cs.enforce_constraint(lc!() + b, lc!() + b, lc!() + b2)?;
             Ok(())
-        } else {
-            Err(eyre::eyre!("invalid proof"))
+        }
+    }
+
+    /// A small circuit with a proving key (libsnark flavour), its matrices and a valid witness.
+    pub(crate) struct TestCircuit<P: Pairing> {
+        pub(crate) pk: ProvingKey<P>,
+        pub(crate) matrices: ConstraintMatrices<P::ScalarField>,
+        pub(crate) witness: Vec<P::ScalarField>,
+    }
+
+    impl<P: Pairing> TestCircuit<P> {
+        pub(crate) fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+            let circuit = MulCircuit {
+                a: P::ScalarField::rand(rng),
+                b: P::ScalarField::rand(rng),
+            };
+            let (pk, _) = ark_groth16::Groth16::<P>::circuit_specific_setup(circuit, rng).unwrap();
+            let cs = ConstraintSystem::new_ref();
+            cs.set_optimization_goal(OptimizationGoal::Constraints);
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            cs.finalize();
+            let matrices = cs.to_matrices().unwrap();
+            let prover = cs.borrow().unwrap();
+            let witness = [
+                prover.instance_assignment.as_slice(),
+                prover.witness_assignment.as_slice(),
+            ]
+            .concat();
+            Self {
+                pk,
+                matrices,
+                witness,
+            }
+        }
+
+        pub(crate) fn public_inputs(&self) -> &[P::ScalarField] {
+            &self.witness[1..self.matrices.num_instance_variables]
+        }
+
+        pub(crate) fn prove<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Proof<P> {
+            Groth16::<P>::prove::<LibSnarkReduction>(
+                &self.pk,
+                P::ScalarField::rand(rng),
+                P::ScalarField::rand(rng),
+                &self.matrices,
+                &self.witness,
+            )
+            .unwrap()
         }
     }
 }
@@ -0,0 +1,365 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::ProvingKey;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{G1Query, Groth16Error};
+
+/// The multi-scalar multiplications (MSMs) of the prover.
+///
+/// The prover calls the backend for every chunk of a query of the proving key, see
+/// [`ProvingKeySource`](crate::ProvingKeySource). `offset` is the index of the first base of the
+/// chunk in the query, so backends with precomputed data for the proving key can ignore `bases`.
+///
+/// [`ArkworksMsm`] is the default backend, [`PrecomputedMsm`] trades memory for proving speed.
+pub trait MsmBackend<P: Pairing>: Sync {
+    /// Computes `sum_i scalars[i] * bases[i]` for the chunk of the G1 `query` starting at `offset`.
+    fn msm_g1(
+        &self,
+        query: G1Query,
+        offset: usize,
+        bases: &[P::G1Affine],
+        scalars: &[P::ScalarField],
+    ) -> Result<P::G1, Groth16Error>;
+
+    /// Computes `sum_i scalars[i] * bases[i]` for the chunk of the `b_g2_query` starting at `offset`.
+    fn msm_g2(
+        &self,
+        offset: usize,
+        bases: &[P::G2Affine],
+        scalars: &[P::ScalarField],
+    ) -> Result<P::G2, Groth16Error>;
+}
+
+/// The MSMs of arkworks ([`VariableBaseMSM::msm_unchecked`]) without any precomputation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ArkworksMsm;
+
+impl<P: Pairing> MsmBackend<P> for ArkworksMsm {
+    fn msm_g1(
+        &self,
+        _query: G1Query,
+        _offset: usize,
+        bases: &[P::G1Affine],
+        scalars: &[P::ScalarField],
+    ) -> Result<P::G1, Groth16Error> {
+        Ok(P::G1::msm_unchecked(bases, scalars))
+    }
+
+    fn msm_g2(
+        &self,
+        _offset: usize,
+        bases: &[P::G2Affine],
+        scalars: &[P::ScalarField],
+    ) -> Result<P::G2, Groth16Error> {
+        Ok(P::G2::msm_unchecked(bases, scalars))
+    }
+}
+
+/// The multiples `2^(c * j) * base` of every base for all windows `j` of `c` bits.
+#[derive(Debug, Clone)]
+struct FixedBaseTable<C: CurveGroup> {
+    len: usize,
+    num_windows: usize,
+    /// The multiples of base `i` are stored at `i * num_windows..(i + 1) * num_windows`.
+    multiples: Vec<C::Affine>,
+}
+
+impl<C: CurveGroup> FixedBaseTable<C> {
+    fn new(bases: &[C::Affine], window_bits: usize) -> Self {
+        let num_windows = (C::ScalarField::MODULUS_BIT_SIZE as usize).div_ceil(window_bits);
+        let multiples_of = |base: &C::Affine| {
+            let mut multiples = Vec::with_capacity(num_windows);
+            let mut multiple = (*base).into();
+            for _ in 0..num_windows {
+                multiples.push(multiple);
+                for _ in 0..window_bits {
+                    multiple.double_in_place();
+                }
+            }
+            C::normalize_batch(&multiples)
+        };
+        #[cfg(feature = "parallel")]
+        let multiples = bases.par_iter().flat_map_iter(multiples_of).collect();
+        #[cfg(not(feature = "parallel"))]
+        let multiples = bases.iter().flat_map(multiples_of).collect();
+        Self {
+            len: bases.len(),
+            num_windows,
+            multiples,
+        }
+    }
+
+    /// Computes the MSM with the bases `offset..offset + scalars.len()` in a single pass of bucket
+    /// accumulation, as the doublings between the windows are part of the table.
+    fn msm(
+        &self,
+        offset: usize,
+        scalars: &[C::ScalarField],
+        window_bits: usize,
+    ) -> Result<C, Groth16Error> {
+        if offset + scalars.len() > self.len {
+            return Err(Groth16Error::ProvingKeyMismatch {
+                expected: offset + scalars.len(),
+                actual: self.len,
+            });
+        }
+        let multiples = &self.multiples[offset * self.num_windows..];
+        let msm_chunk = |(chunk_idx, scalars): (usize, &[C::ScalarField])| {
+            let mut buckets = vec![C::zero(); (1 << window_bits) - 1];
+            for (idx, scalar) in scalars.iter().enumerate() {
+                let scalar = scalar.into_bigint();
+                let base_idx = (chunk_idx * BUCKET_CHUNK_SIZE + idx) * self.num_windows;
+                for (window, multiple) in multiples[base_idx..base_idx + self.num_windows]
+                    .iter()
+                    .enumerate()
+                {
+                    let digit = window_digit(&scalar, window * window_bits, window_bits);
+                    if digit != 0 {
+                        buckets[digit - 1] += multiple;
+                    }
+                }
+            }
+            // sum_d d * bucket[d] with running sums
+            let mut running = C::zero();
+            let mut acc = C::zero();
+            for bucket in buckets.into_iter().rev() {
+                running += bucket;
+                acc += running;
+            }
+            acc
+        };
+        #[cfg(feature = "parallel")]
+        let acc = scalars
+            .par_chunks(BUCKET_CHUNK_SIZE)
+            .enumerate()
+            .map(msm_chunk)
+            .sum();
+        #[cfg(not(feature = "parallel"))]
+        let acc = scalars
+            .chunks(BUCKET_CHUNK_SIZE)
+            .enumerate()
+            .map(msm_chunk)
+            .sum();
+        Ok(acc)
+    }
+}
+
+/// The number of scalars accumulated into the same buckets.
+///
+/// Every chunk allocates `2^window_bits - 1` buckets, so the window size is bounded by
+/// [`PrecomputedMsm::MAX_WINDOW_BITS`] to keep the buckets of all workers small.
+const BUCKET_CHUNK_SIZE: usize = 1 << 14;
+
+/// Returns the `window_bits` bits of `scalar` starting at bit `start`.
+fn window_digit<B: BigInteger>(scalar: &B, start: usize, window_bits: usize) -> usize {
+    let limbs = scalar.as_ref();
+    let (limb, shift) = (start / 64, start % 64);
+    let mut digit = limbs[limb] >> shift;
+    if shift + window_bits > 64 && limb + 1 < limbs.len() {
+        digit |= limbs[limb + 1] << (64 - shift);
+    }
+    (digit & ((1 << window_bits) - 1)) as usize
+}
+
+/// An [`MsmBackend`] with fixed-base tables precomputed from a [`ProvingKey`].
+///
+/// For every base of the proving key, the table stores the multiples `2^(c * j) * base` for all
+/// windows `j` of `c = window_bits` bits. An MSM then needs a single pass of bucket accumulation
+/// instead of one pass per window, and no doublings. The tables need `ceil(bits / c)` times the
+/// memory of the proving key, where `bits` is the size of the scalar field, so larger windows use
+/// less memory but more buckets.
+///
+/// Building the tables is expensive and should be done once per proving key. The backend can only
+/// be used with the proving key it was built from, other keys result in wrong proofs.
+#[derive(Debug, Clone)]
+pub struct PrecomputedMsm<P: Pairing> {
+    window_bits: usize,
+    a: FixedBaseTable<P::G1>,
+    b_g1: FixedBaseTable<P::G1>,
+    b_g2: FixedBaseTable<P::G2>,
+    l: FixedBaseTable<P::G1>,
+    h: FixedBaseTable<P::G1>,
+}
+
+impl<P: Pairing> PrecomputedMsm<P> {
+    /// The maximum supported window size in bits.
+    ///
+    /// Every worker allocates `2^window_bits - 1` buckets per chunk of scalars, i.e., up to 12 MiB
+    /// for 16 bits, but already GiBs for 24 bits.
+    pub const MAX_WINDOW_BITS: usize = 16;
+
+    /// Builds the tables for the proving key with a window size depending on the size of the key.
+    pub fn new(pkey: &ProvingKey<P>) -> Self {
+        let num_bases = pkey.a_query.len().max(pkey.h_query.len());
+        Self::with_window_bits(pkey, Self::default_window_bits(num_bases))
+    }
+
+    /// Returns the window size for tables of `num_bases` bases, at most [`PrecomputedMsm::MAX_WINDOW_BITS`].
+    fn default_window_bits(num_bases: usize) -> usize {
+        (num_bases.max(1).ilog2() as usize).clamp(4, Self::MAX_WINDOW_BITS)
+    }
+
+    /// Builds the tables for the proving key with windows of `window_bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_bits` is zero or larger than [`PrecomputedMsm::MAX_WINDOW_BITS`].
+    pub fn with_window_bits(pkey: &ProvingKey<P>, window_bits: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_WINDOW_BITS).contains(&window_bits),
+            "window_bits must be between 1 and {}",
+            Self::MAX_WINDOW_BITS
+        );
+        Self {
+            window_bits,
+            a: FixedBaseTable::new(&pkey.a_query, window_bits),
+            b_g1: FixedBaseTable::new(&pkey.b_g1_query, window_bits),
+            b_g2: FixedBaseTable::new(&pkey.b_g2_query, window_bits),
+            l: FixedBaseTable::new(&pkey.l_query, window_bits),
+            h: FixedBaseTable::new(&pkey.h_query, window_bits),
+        }
+    }
+
+    /// Returns the window size in bits.
+    pub fn window_bits(&self) -> usize {
+        self.window_bits
+    }
+}
+
+impl<P: Pairing> MsmBackend<P> for PrecomputedMsm<P> {
+    fn msm_g1(
+        &self,
+        query: G1Query,
+        offset: usize,
+        _bases: &[P::G1Affine],
+        scalars: &[P::ScalarField],
+    ) -> Result<P::G1, Groth16Error> {
+        let table = match query {
+            G1Query::A => &self.a,
+            G1Query::BG1 => &self.b_g1,
+            G1Query::L => &self.l,
+            G1Query::H => &self.h,
+        };
+        table.msm(offset, scalars, self.window_bits)
+    }
+
+    fn msm_g2(
+        &self,
+        offset: usize,
+        _bases: &[P::G2Affine],
+        scalars: &[P::ScalarField],
+    ) -> Result<P::G2, Groth16Error> {
+        self.b_g2.msm(offset, scalars, self.window_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use ark_bn254::{Bn254, Fr, G1Affine, G1Projective};
+    use ark_ff::UniformRand;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::test_utils::TestCircuit;
+    use crate::{Groth16, LibSnarkReduction, ProverContext};
+
+    #[test]
+    fn precomputed_msm_matches_arkworks() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let bases = (0..100)
+            .map(|_| G1Affine::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let scalars = (0..60)
+            .map(|_| Fr::rand(&mut rng))
+            .chain([Fr::from(0), -Fr::from(1)])
+            .collect::<Vec<_>>();
+        for window_bits in [1, 5, 8, 13] {
+            let table = FixedBaseTable::<G1Projective>::new(&bases, window_bits);
+            for offset in [0, 17, 38] {
+                let should = G1Projective::msm_unchecked(&bases[offset..], &scalars);
+                let is = table.msm(offset, &scalars, window_bits).unwrap();
+                assert_eq!(is, should, "window_bits {window_bits}, offset {offset}");
+            }
+            assert!(matches!(
+                table.msm(39, &scalars, window_bits),
+                Err(Groth16Error::ProvingKeyMismatch {
+                    expected: 101,
+                    actual: 100
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn window_bits_are_bounded() {
+        for num_bases in [0, 1, 1 << 10, 1 << 20, 1 << 28, usize::MAX] {
+            let window_bits = PrecomputedMsm::<Bn254>::default_window_bits(num_bases);
+            assert!((4..=PrecomputedMsm::<Bn254>::MAX_WINDOW_BITS).contains(&window_bits));
+        }
+        assert_eq!(PrecomputedMsm::<Bn254>::default_window_bits(1 << 10), 10);
+        let window_bits = PrecomputedMsm::<Bn254>::default_window_bits(1 << 28);
+        assert_eq!(window_bits, 16);
+        // the buckets of a chunk stay below 16 MiB for G2
+        let bucket_bytes =
+            ((1 << window_bits) - 1) * std::mem::size_of::<ark_bn254::G2Projective>();
+        assert!(bucket_bytes < 16 << 20, "{bucket_bytes}");
+    }
+
+    #[test]
+    #[should_panic(expected = "window_bits must be between 1 and 16")]
+    fn window_bits_above_max_are_rejected() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        PrecomputedMsm::with_window_bits(&circuit.pk, PrecomputedMsm::<Bn254>::MAX_WINDOW_BITS + 1);
+    }
+
+    #[test]
+    fn precomputed_backend_proof_matches_prove() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let (r, s) = (Fr::from(42), Fr::from(1337));
+        let ctx =
+            ProverContext::<Bn254, LibSnarkReduction>::new(&circuit.pk, &circuit.matrices).unwrap();
+        let backend = PrecomputedMsm::new(&circuit.pk);
+
+        let is_proof = Groth16::prove_with_backend(
+            &ctx,
+            &circuit.pk,
+            &backend,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        let should_proof = Groth16::prove_with_context(
+            &ctx,
+            &circuit.pk,
+            r,
+            s,
+            &circuit.matrices,
+            &circuit.witness,
+        )
+        .unwrap();
+        assert_eq!(is_proof, should_proof);
+
+        let h = Groth16::compute_h(&ctx, &circuit.matrices, &circuit.witness).unwrap();
+        let streamed = Groth16::compute_contributions_with_backend(
+            &circuit.pk,
+            &backend,
+            &h,
+            &circuit.witness,
+            NonZeroUsize::new(2).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            streamed,
+            Groth16::<Bn254>::compute_contributions(&circuit.pk, &h, &circuit.witness).unwrap()
+        );
+    }
+}
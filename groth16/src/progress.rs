@@ -0,0 +1,90 @@
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::Groth16Error;
+
+/// The phases of Groth16 proof generation reported to a [`ProgressHook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProvePhase {
+    /// Checking the witness against the constraint matrices (see [`ProveOptions::check_witness`](crate::ProveOptions::check_witness)).
+    CheckWitness,
+    /// Evaluating the constraint matrices at the witness.
+    EvaluateConstraints,
+    /// Computing the QAP witness with (inverse) FFTs.
+    Fft,
+    /// Computing the MSMs of the witness and the QAP witness with the proving key.
+    Msm,
+    /// Combining the MSMs with the randomness `r` and `s` into the proof.
+    Combine,
+    /// Verifying the proof (see [`ProveOptions::verify_after`](crate::ProveOptions::verify_after)).
+    Verify,
+}
+
+impl fmt::Display for ProvePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phase = match self {
+            ProvePhase::CheckWitness => "check witness",
+            ProvePhase::EvaluateConstraints => "evaluate constraints",
+            ProvePhase::Fft => "fft",
+            ProvePhase::Msm => "msm",
+            ProvePhase::Combine => "combine",
+            ProvePhase::Verify => "verify",
+        };
+        f.write_str(phase)
+    }
+}
+
+/// A hook that is polled by the prover before every [`ProvePhase`].
+///
+/// Can be used to report progress and to cancel long running proofs. Cancelling returns
+/// [`Groth16Error::Cancelled`] with the phase that was not started. A running phase is never
+/// interrupted.
+///
+/// The trait is implemented for closures taking a [`ProvePhase`].
+///
+/// # Example
+/// ```rust
+/// # use std::ops::ControlFlow;
+/// # use std::sync::atomic::{AtomicBool, Ordering};
+/// # use taceo_groth16::{ProgressHook, ProvePhase};
+/// let cancelled = AtomicBool::new(false);
+/// let hook = |phase: ProvePhase| {
+///     tracing::info!("starting {phase}");
+///     if cancelled.load(Ordering::Relaxed) {
+///         ControlFlow::Break(())
+///     } else {
+///         ControlFlow::Continue(())
+///     }
+/// };
+/// # fn takes_hook(_: &dyn ProgressHook) {}
+/// # takes_hook(&hook);
+/// ```
+pub trait ProgressHook: Sync {
+    /// Called before the prover starts `phase`. Returning [`ControlFlow::Break`] cancels proof generation.
+    fn on_phase(&self, phase: ProvePhase) -> ControlFlow<()>;
+}
+
+impl<F> ProgressHook for F
+where
+    F: Fn(ProvePhase) -> ControlFlow<()> + Sync,
+{
+    fn on_phase(&self, phase: ProvePhase) -> ControlFlow<()> {
+        self(phase)
+    }
+}
+
+/// A [`ProgressHook`] that never cancels.
+impl ProgressHook for () {
+    fn on_phase(&self, _: ProvePhase) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Polls the hook for `phase` and returns [`Groth16Error::Cancelled`] if the hook requests cancellation.
+pub(crate) fn checkpoint(hook: &dyn ProgressHook, phase: ProvePhase) -> Result<(), Groth16Error> {
+    tracing::trace!("entering phase {phase}");
+    match hook.on_phase(phase) {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(Groth16Error::Cancelled(phase)),
+    }
+}
@@ -1,13 +1,15 @@
 use ark_ec::pairing::Pairing;
-use ark_ff::{FftField, Field, One};
+use ark_ff::PrimeField;
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::r1cs::{ConstraintMatrices, Matrix};
+#[cfg(feature = "parallel")]
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
 };
 use tracing::instrument;
 
-use crate::rayon_join3;
+use crate::progress::checkpoint;
+use crate::{Groth16Error, ProgressHook, ProvePhase, cfg_iter, cfg_iter_mut, join};
 
 use super::root_of_unity_for_groth16;
 
@@ -15,11 +17,34 @@ use super::root_of_unity_for_groth16;
 /// Refer to <https://docs.rs/ark-groth16/latest/ark_groth16/r1cs_to_qap/trait.R1CSToQAP.html> for more details.
 /// We do not implement the other methods of the arkworks trait, as we do not need them during proof generation.
 pub trait R1CSToQAP {
+    /// The witness independent data of the reduction (evaluation domains, roots of unity, ...).
+    /// It only depends on the size of the constraint system and can be reused across proofs, see [`ProverContext`](crate::ProverContext).
+    type Domain<F: PrimeField>: Clone + Send + Sync;
+
+    /// Computes the [`R1CSToQAP::Domain`] for a constraint system with `num_constraints` constraints and `num_inputs` instance variables.
+    fn domain<F: PrimeField>(
+        num_constraints: usize,
+        num_inputs: usize,
+    ) -> Result<Self::Domain<F>, Groth16Error>;
+
     /// Computes a QAP witness corresponding to the R1CS witness, using the provided `ConstraintMatrices`.
     fn witness_map_from_matrices<P: Pairing>(
         matrices: &ConstraintMatrices<P::ScalarField>,
         witness: &[P::ScalarField],
-    ) -> eyre::Result<Vec<P::ScalarField>>;
+    ) -> Result<Vec<P::ScalarField>, Groth16Error> {
+        let domain = Self::domain(matrices.num_constraints, matrices.num_instance_variables)?;
+        Self::witness_map_with_domain::<P>(&domain, matrices, witness, &())
+    }
+
+    /// Computes a QAP witness corresponding to the R1CS witness, using the provided `ConstraintMatrices` and a precomputed [`R1CSToQAP::Domain`].
+    ///
+    /// The `hook` is polled before evaluating the constraints and before the FFTs.
+    fn witness_map_with_domain<P: Pairing>(
+        domain: &Self::Domain<P::ScalarField>,
+        matrices: &ConstraintMatrices<P::ScalarField>,
+        witness: &[P::ScalarField],
+        hook: &dyn ProgressHook,
+    ) -> Result<Vec<P::ScalarField>, Groth16Error>;
 }
 
 /// Implements the witness map used by snarkjs. The arkworks witness map calculates the
@@ -31,35 +56,58 @@ pub trait R1CSToQAP {
 /// Based on <https://github.com/arkworks-rs/circom-compat/>.
 pub struct CircomReduction;
 
+/// The [`R1CSToQAP::Domain`] of the [`CircomReduction`].
+///
+/// Holds the evaluation domain (with the generator used by snarkjs) and the powers of the root of
+/// unity of the domain twice as large.
+#[derive(Debug, Clone)]
+pub struct CircomDomain<F: PrimeField> {
+    domain: GeneralEvaluationDomain<F>,
+    roots_to_power_domain: Vec<F>,
+}
+
 impl R1CSToQAP for CircomReduction {
+    type Domain<F: PrimeField> = CircomDomain<F>;
+
+    #[instrument(level = "debug", name = "circom domain", skip_all)]
+    fn domain<F: PrimeField>(
+        num_constraints: usize,
+        num_inputs: usize,
+    ) -> Result<Self::Domain<F>, Groth16Error> {
+        let mut domain = GeneralEvaluationDomain::<F>::new(num_constraints + num_inputs)
+            .ok_or(Groth16Error::PolynomialDegreeTooLarge)?;
+        let domain_size = domain.size();
+        let power = domain_size.ilog2() as usize;
+        let root_of_unity = root_of_unity_for_groth16(power, &mut domain);
+        let mut roots_to_power_domain = Vec::with_capacity(domain_size);
+        let mut c = F::one();
+        for _ in 0..domain_size {
+            roots_to_power_domain.push(c);
+            c *= root_of_unity;
+        }
+        Ok(CircomDomain {
+            domain,
+            roots_to_power_domain,
+        })
+    }
+
     #[instrument(level = "debug", name = "witness map from matrices", skip_all)]
-    fn witness_map_from_matrices<P: Pairing>(
+    fn witness_map_with_domain<P: Pairing>(
+        domain: &Self::Domain<P::ScalarField>,
         matrices: &ConstraintMatrices<P::ScalarField>,
         witness: &[P::ScalarField],
-    ) -> eyre::Result<Vec<P::ScalarField>> {
+        hook: &dyn ProgressHook,
+    ) -> Result<Vec<P::ScalarField>, Groth16Error> {
         let num_constraints = matrices.num_constraints;
         let num_inputs = matrices.num_instance_variables;
-        let mut domain =
-            GeneralEvaluationDomain::<P::ScalarField>::new(num_constraints + num_inputs)
-                .ok_or(eyre::eyre!("Polynomial Degree too large"))?;
+        let CircomDomain {
+            domain,
+            roots_to_power_domain,
+        } = domain;
         let domain_size = domain.size();
-        let power = domain_size.ilog2() as usize;
-        let eval_constraint_span =
-            tracing::debug_span!("evaluate constraints + root of unity computation").entered();
-        let (roots_to_power_domain, a, b) = rayon_join3!(
-            || {
-                let root_of_unity_span =
-                    tracing::debug_span!("root of unity computation").entered();
-                let root_of_unity = root_of_unity_for_groth16(power, &mut domain);
-                let mut roots = Vec::with_capacity(domain_size);
-                let mut c = P::ScalarField::one();
-                for _ in 0..domain_size {
-                    roots.push(c);
-                    c *= root_of_unity;
-                }
-                root_of_unity_span.exit();
-                roots
-            },
+        checkpoint(hook, ProvePhase::EvaluateConstraints)?;
+        let eval_constraint_span = tracing::debug_span!("evaluate constraints").entered();
+        let (a, b) = join(
             || {
                 let eval_constraint_span_a =
                     tracing::debug_span!("evaluate constraints - a").entered();
@@ -85,22 +133,23 @@ impl R1CSToQAP for CircomReduction {
                 );
                 eval_constraint_span_b.exit();
                 result
-            }
+            },
         );
 
         eval_constraint_span.exit();
+        checkpoint(hook, ProvePhase::Fft)?;
         let mut a_result = a.clone();
         let mut b_result = b.clone();
-        let (mut ab, c) = rayon::join(
+        let (mut ab, c) = join(
             || {
-                let (a, b) = rayon::join(
+                let (a, b) = join(
                     || {
                         let a_span =
                             tracing::debug_span!("a: distribute powers mul a (fft/ifft)").entered();
                         domain.ifft_in_place(&mut a_result);
                         distribute_powers_and_mul_by_const::<P>(
                             &mut a_result,
-                            &roots_to_power_domain,
+                            roots_to_power_domain,
                         );
                         domain.fft_in_place(&mut a_result);
                         a_span.exit();
@@ -112,7 +161,7 @@ impl R1CSToQAP for CircomReduction {
                         domain.ifft_in_place(&mut b_result);
                         distribute_powers_and_mul_by_const::<P>(
                             &mut b_result,
-                            &roots_to_power_domain,
+                            roots_to_power_domain,
                         );
                         domain.fft_in_place(&mut b_result);
                         b_span.exit();
@@ -136,8 +185,8 @@ impl R1CSToQAP for CircomReduction {
                 domain.ifft_in_place(&mut ab);
                 ifft_span.exit();
                 let dist_pows_span = tracing::debug_span!("c: dist pows").entered();
-                ab.par_iter_mut()
-                    .zip_eq(roots_to_power_domain.par_iter())
+                cfg_iter_mut!(ab)
+                    .zip(cfg_iter!(roots_to_power_domain))
                     .for_each(|(share, pow)| {
                         *share *= *pow;
                     });
@@ -150,7 +199,7 @@ impl R1CSToQAP for CircomReduction {
         );
 
         let compute_ab_span = tracing::debug_span!("compute ab").entered();
-        ab.par_iter_mut().zip_eq(c.par_iter()).for_each(|(a, b)| {
+        cfg_iter_mut!(ab).zip(cfg_iter!(c)).for_each(|(a, b)| {
             *a -= *b;
         });
         compute_ab_span.exit();
@@ -164,8 +213,7 @@ fn evaluate_constraint<P: Pairing>(
     num_constraints: usize,
     witness: &[P::ScalarField],
 ) -> Vec<P::ScalarField> {
-    let mut result = matrix
-        .par_iter()
+    let mut result = cfg_iter!(matrix)
         .take(num_constraints)
         .map(|lhs| {
             let mut acc = P::ScalarField::default();
@@ -186,25 +234,61 @@ fn evaluate_constraint<P: Pairing>(
 /// Based on <https://github.com/arkworks-rs/groth16/>.
 pub struct LibSnarkReduction;
 
+/// The [`R1CSToQAP::Domain`] of the [`LibSnarkReduction`].
+///
+/// Holds the evaluation domain, its coset shifted by the multiplicative generator of the field and
+/// the inverse of the vanishing polynomial evaluated over the coset.
+#[derive(Debug, Clone)]
+pub struct LibSnarkDomain<F: PrimeField> {
+    domain: GeneralEvaluationDomain<F>,
+    coset_domain: GeneralEvaluationDomain<F>,
+    vanishing_polynomial_over_coset: F,
+}
+
 impl R1CSToQAP for LibSnarkReduction {
+    type Domain<F: PrimeField> = LibSnarkDomain<F>;
+
+    #[instrument(level = "debug", name = "libsnark domain", skip_all)]
+    fn domain<F: PrimeField>(
+        num_constraints: usize,
+        num_inputs: usize,
+    ) -> Result<Self::Domain<F>, Groth16Error> {
+        let domain = GeneralEvaluationDomain::<F>::new(num_constraints + num_inputs)
+            .ok_or(Groth16Error::PolynomialDegreeTooLarge)?;
+        let coset_domain = domain
+            .get_coset(F::GENERATOR)
+            .expect("generator has always inverse");
+        let vanishing_polynomial_over_coset = domain
+            .evaluate_vanishing_polynomial(F::GENERATOR)
+            .inverse()
+            .expect("Inverse exists");
+        Ok(LibSnarkDomain {
+            domain,
+            coset_domain,
+            vanishing_polynomial_over_coset,
+        })
+    }
+
     #[instrument(level = "debug", name = "witness map from matrices", skip_all)]
-    fn witness_map_from_matrices<P: Pairing>(
+    fn witness_map_with_domain<P: Pairing>(
+        domain: &Self::Domain<P::ScalarField>,
         matrices: &ConstraintMatrices<P::ScalarField>,
         witness: &[P::ScalarField],
-    ) -> eyre::Result<Vec<P::ScalarField>> {
+        hook: &dyn ProgressHook,
+    ) -> Result<Vec<P::ScalarField>, Groth16Error> {
         let num_constraints = matrices.num_constraints;
         let num_inputs = matrices.num_instance_variables;
-        let domain = GeneralEvaluationDomain::<P::ScalarField>::new(num_constraints + num_inputs)
-            .ok_or(eyre::eyre!("Polynomial Degree too large"))?;
+        let LibSnarkDomain {
+            domain,
+            coset_domain,
+            vanishing_polynomial_over_coset,
+        } = domain;
         let domain_size = domain.size();
 
-        let coset_domain = domain
-            .get_coset(P::ScalarField::GENERATOR)
-            .expect("generator has always inverse");
-
-        let (mut ab, c) = rayon::join(
+        checkpoint(hook, ProvePhase::EvaluateConstraints)?;
+        let ((mut a, mut b), mut c) = join(
             || {
-                let (a, b) = rayon::join(
+                join(
                     || {
                         let mut a = evaluate_constraint::<P>(
                             domain_size,
@@ -214,20 +298,39 @@ impl R1CSToQAP for LibSnarkReduction {
                         );
                         a[num_constraints..num_constraints + num_inputs]
                             .clone_from_slice(&witness[..num_inputs]);
-                        domain.ifft_in_place(&mut a);
-                        coset_domain.fft_in_place(&mut a);
                         a
                     },
                     || {
-                        let mut b = evaluate_constraint::<P>(
+                        evaluate_constraint::<P>(
                             domain_size,
                             &matrices.b,
                             matrices.num_constraints,
                             witness,
-                        );
+                        )
+                    },
+                )
+            },
+            || {
+                evaluate_constraint::<P>(
+                    domain_size,
+                    &matrices.c,
+                    matrices.num_constraints,
+                    witness,
+                )
+            },
+        );
+
+        checkpoint(hook, ProvePhase::Fft)?;
+        let (mut ab, c) = join(
+            || {
+                join(
+                    || {
+                        domain.ifft_in_place(&mut a);
+                        coset_domain.fft_in_place(&mut a);
+                    },
+                    || {
                         domain.ifft_in_place(&mut b);
                         coset_domain.fft_in_place(&mut b);
-                        b
                     },
                 );
                 a.iter()
@@ -236,26 +339,15 @@ impl R1CSToQAP for LibSnarkReduction {
                     .collect::<Vec<_>>()
             },
             || {
-                let mut c = evaluate_constraint::<P>(
-                    domain_size,
-                    &matrices.c,
-                    matrices.num_constraints,
-                    witness,
-                );
                 domain.ifft_in_place(&mut c);
                 coset_domain.fft_in_place(&mut c);
                 c
             },
         );
 
-        let vanishing_polynomial_over_coset = domain
-            .evaluate_vanishing_polynomial(P::ScalarField::GENERATOR)
-            .inverse()
-            .expect("Inverse exists");
-
-        ab.par_iter_mut().zip(c.par_iter()).for_each(|(ab_i, c_i)| {
+        cfg_iter_mut!(ab).zip(cfg_iter!(c)).for_each(|(ab_i, c_i)| {
             *ab_i -= *c_i;
-            *ab_i *= vanishing_polynomial_over_coset;
+            *ab_i *= *vanishing_polynomial_over_coset;
         });
 
         coset_domain.ifft_in_place(&mut ab);
@@ -0,0 +1,194 @@
+//! Test vectors for checking the [`CircomReduction`] against proofs created by snarkjs.
+//!
+//! Every test vector is a directory with a snarkjs proving key (`circuit.zkey`), a witness (`witness.wtns`)
+//! and a proof created by snarkjs for that witness (`circom.proof`), like the known-answer tests of
+//! `taceo-circom-types` in `circom-types/kats/groth16/<curve>`. Load them with [`TestVector::from_dir`].
+//!
+//! snarkjs samples the randomness `r` and `s` of its proofs, so the proofs of this crate cannot be
+//! compared to `circom.proof` directly. Instead, [`TestVector::check`] verifies both the snarkjs proof
+//! and a proof of this crate under the proving key of the vector. The vectors do not contain the
+//! evaluations of `h` or proofs with fixed randomness, so they are not compared.
+use std::path::Path;
+
+use ark_ec::pairing::Pairing;
+use circom_types::CheckElement;
+use circom_types::traits::CircomArkworksPairingBridge;
+
+use crate::{
+    CircomReduction, ConstraintMatrices, Groth16, Groth16Error, Proof, ProverContext, ProvingKey,
+};
+
+/// Errors that can occur when loading or checking a [`TestVector`].
+#[derive(Debug, thiserror::Error)]
+pub enum TestVectorError {
+    /// A file of the vector could not be read.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The proving key could not be parsed.
+    #[error(transparent)]
+    Zkey(#[from] circom_types::ZkeyParserError),
+    /// The witness could not be parsed.
+    #[error(transparent)]
+    Witness(#[from] circom_types::WitnessParserError),
+    /// The proof could not be parsed.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Proving or verifying failed.
+    #[error(transparent)]
+    Groth16(#[from] Groth16Error),
+    /// The proof created by snarkjs does not verify.
+    #[error("snarkjs proof does not verify")]
+    SnarkjsProofRejected,
+}
+
+/// A snarkjs test vector, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct TestVector<P: Pairing> {
+    /// The name of the vector.
+    pub name: &'static str,
+    /// The constraint matrices from the proving key.
+    pub matrices: ConstraintMatrices<P::ScalarField>,
+    /// The proving key.
+    pub pk: ProvingKey<P>,
+    /// The full witness, including the leading one and the public inputs.
+    pub witness: Vec<P::ScalarField>,
+    /// The proof created by snarkjs with unknown randomness.
+    pub snarkjs_proof: Proof<P>,
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> TestVector<P> {
+    /// Loads a test vector from the contents of its files.
+    pub fn from_bytes(
+        name: &'static str,
+        zkey: &[u8],
+        witness: &[u8],
+        proof: &[u8],
+    ) -> Result<Self, TestVectorError> {
+        let zkey = circom_types::groth16::Zkey::<P>::from_reader(zkey, CheckElement::Yes)?;
+        let (matrices, pk) = zkey.into();
+        let witness = circom_types::Witness::<P::ScalarField>::from_reader(witness)?.values;
+        let snarkjs_proof =
+            serde_json::from_slice::<circom_types::groth16::Proof<P>>(proof)?.into();
+        Ok(Self {
+            name,
+            matrices,
+            pk,
+            witness,
+            snarkjs_proof,
+        })
+    }
+
+    /// Loads a test vector from the files `circuit.zkey`, `witness.wtns` and `circom.proof` in `dir`.
+    pub fn from_dir(name: &'static str, dir: impl AsRef<Path>) -> Result<Self, TestVectorError> {
+        let dir = dir.as_ref();
+        Self::from_bytes(
+            name,
+            &std::fs::read(dir.join("circuit.zkey"))?,
+            &std::fs::read(dir.join("witness.wtns"))?,
+            &std::fs::read(dir.join("circom.proof"))?,
+        )
+    }
+}
+
+impl<P: Pairing> TestVector<P> {
+    /// Returns the public inputs of the witness.
+    pub fn public_inputs(&self) -> &[P::ScalarField] {
+        &self.witness[1..self.matrices.num_instance_variables]
+    }
+
+    /// Checks the [`CircomReduction`] and the prover of this crate against the vector.
+    ///
+    /// The snarkjs proof must verify under the proving key and a proof of this crate must verify
+    /// as well.
+    pub fn check(&self) -> Result<(), TestVectorError> {
+        if Groth16::verify(&self.pk.vk, &self.snarkjs_proof, self.public_inputs()).is_err() {
+            return Err(TestVectorError::SnarkjsProofRejected);
+        }
+
+        let ctx = ProverContext::<P, CircomReduction>::new(&self.pk, &self.matrices)?;
+        let (r, s) = (P::ScalarField::from(42), P::ScalarField::from(1337));
+        let proof =
+            Groth16::prove_with_context(&ctx, &self.pk, r, s, &self.matrices, &self.witness)?;
+        Groth16::verify(&self.pk.vk, &proof, self.public_inputs())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The known-answer tests of `taceo-circom-types`.
+    const KATS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../circom-types/kats/groth16");
+
+    fn bn254() -> TestVector<ark_bn254::Bn254> {
+        TestVector::from_dir("multiplier2", format!("{KATS}/bn254")).unwrap()
+    }
+
+    fn bls12_381() -> TestVector<ark_bls12_381::Bls12_381> {
+        TestVector::from_dir("multiplier2", format!("{KATS}/bls12_381")).unwrap()
+    }
+
+    #[test]
+    fn kat_vectors_match() {
+        bn254().check().unwrap();
+        bls12_381().check().unwrap();
+        assert!(matches!(
+            TestVector::<ark_bn254::Bn254>::from_dir("missing", KATS),
+            Err(TestVectorError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn check_witness_uses_qap_without_c_matrix() {
+        let vector = bn254();
+        let ctx = ProverContext::<_, CircomReduction>::new(&vector.pk, &vector.matrices).unwrap();
+        let (r, s) = (ark_bn254::Fr::from(42), ark_bn254::Fr::from(1337));
+        let options = crate::ProveOptions {
+            check_witness: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Groth16::<ark_bn254::Bn254>::check_witness(&vector.matrices, &vector.witness),
+            Err(Groth16Error::MissingCMatrix)
+        ));
+        Groth16::prove_with_options(
+            &ctx,
+            &vector.pk,
+            r,
+            s,
+            &vector.matrices,
+            &vector.witness,
+            &options,
+        )
+        .unwrap();
+
+        // the witness is [1, c, a, b], so c != a * b
+        let mut witness = vector.witness.clone();
+        witness[1] += ark_bn254::Fr::from(1);
+        assert!(matches!(
+            Groth16::prove_with_options(
+                &ctx,
+                &vector.pk,
+                r,
+                s,
+                &vector.matrices,
+                &witness,
+                &options,
+            ),
+            Err(Groth16Error::UnsatisfiedWitness)
+        ));
+        assert!(matches!(
+            Groth16::<ark_bn254::Bn254>::check_witness(&vector.matrices, &witness[1..]),
+            Err(Groth16Error::InvalidWitnessLength { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_witness_is_rejected() {
+        let mut vector = bn254();
+        let last = vector.witness.len() - 1;
+        vector.witness[last] += ark_bn254::Fr::from(1);
+        assert!(vector.check().is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::ops::Neg;
+use std::sync::Arc;
+
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::{PreparedVerifyingKey, Proof, VerifyingKey};
+
+use crate::Groth16Error;
+
+/// A Groth16 verifier holding a [`PreparedVerifyingKey`].
+///
+/// Preparing the verifying key precomputes the G2 line coefficients for `gamma` and `delta` and
+/// the pairing `e(alpha, beta)`. [`Groth16::verify`](crate::Groth16::verify) repeats this work on
+/// every call, whereas a `Groth16Verifier` does it once in [`Groth16Verifier::new`] and reuses it
+/// for all subsequent verifications.
+///
+/// The prepared key is stored behind an [`Arc`], so cloning the verifier is cheap and it can be
+/// shared across threads.
+#[derive(Debug)]
+pub struct Groth16Verifier<P: Pairing> {
+    pvk: Arc<PreparedVerifyingKey<P>>,
+}
+
+impl<P: Pairing> Clone for Groth16Verifier<P> {
+    fn clone(&self) -> Self {
+        Self {
+            pvk: Arc::clone(&self.pvk),
+        }
+    }
+}
+
+impl<P: Pairing> TryFrom<PreparedVerifyingKey<P>> for Groth16Verifier<P> {
+    type Error = Groth16Error;
+
+    fn try_from(pvk: PreparedVerifyingKey<P>) -> Result<Self, Self::Error> {
+        if pvk.vk.gamma_abc_g1.is_empty() {
+            return Err(Groth16Error::MalformedVerifyingKey);
+        }
+        Ok(Self { pvk: Arc::new(pvk) })
+    }
+}
+
+impl<P: Pairing> TryFrom<&VerifyingKey<P>> for Groth16Verifier<P> {
+    type Error = Groth16Error;
+
+    fn try_from(vk: &VerifyingKey<P>) -> Result<Self, Self::Error> {
+        Self::new(vk)
+    }
+}
+
+impl<P: Pairing> Groth16Verifier<P> {
+    /// Prepares the provided [`VerifyingKey`] and creates a new verifier.
+    ///
+    /// Returns [`Groth16Error::MalformedVerifyingKey`] if `gamma_abc_g1` is empty, as it must at least contain the
+    /// element for the constant one.
+    pub fn new(vk: &VerifyingKey<P>) -> Result<Self, Groth16Error> {
+        if vk.gamma_abc_g1.is_empty() {
+            return Err(Groth16Error::MalformedVerifyingKey);
+        }
+        PreparedVerifyingKey {
+            vk: vk.clone(),
+            alpha_g1_beta_g2: P::pairing(vk.alpha_g1, vk.beta_g2).0,
+            gamma_g2_neg_pc: vk.gamma_g2.into_group().neg().into_affine().into(),
+            delta_g2_neg_pc: vk.delta_g2.into_group().neg().into_affine().into(),
+        }
+        .try_into()
+    }
+
+    /// Returns a reference to the underlying [`PreparedVerifyingKey`].
+    pub fn prepared_verifying_key(&self) -> &PreparedVerifyingKey<P> {
+        &self.pvk
+    }
+
+    /// Returns a reference to the underlying [`VerifyingKey`].
+    pub fn verifying_key(&self) -> &VerifyingKey<P> {
+        &self.pvk.vk
+    }
+
+    /// Returns the number of public inputs expected by the verifying key.
+    pub fn num_public_inputs(&self) -> usize {
+        // `gamma_abc_g1` is not empty, see `Groth16Verifier::new`
+        self.pvk.vk.gamma_abc_g1.len() - 1
+    }
+
+    /// Verifies a Groth16 proof against the prepared verifying key.
+    ///
+    /// The public inputs are accumulated with plain scalar multiplications, so apart from the
+    /// pairing itself no intermediate buffers are allocated.
+    pub fn verify(
+        &self,
+        proof: &Proof<P>,
+        public_inputs: &[P::ScalarField],
+    ) -> Result<(), Groth16Error> {
+        let expected = self.num_public_inputs();
+        if public_inputs.len() != expected {
+            return Err(Groth16Error::InvalidPublicInputLength {
+                expected,
+                actual: public_inputs.len(),
+            });
+        }
+
+        self.verify_with_offset(proof, public_inputs, P::G1::zero())
+    }
+
+    /// Verifies a Groth16 proof where `offset` is added to the accumulated public inputs.
+    ///
+    /// The offset carries the commitments of committed private values, see [`crate::CommitmentProvingKey`].
+    /// The caller checks the number of public inputs.
+    pub(crate) fn verify_with_offset(
+        &self,
+        proof: &Proof<P>,
+        public_inputs: &[P::ScalarField],
+        offset: P::G1,
+    ) -> Result<(), Groth16Error> {
+        let mut g_ic = self.pvk.vk.gamma_abc_g1[0].into_group() + offset;
+        for (input, base) in public_inputs
+            .iter()
+            .zip(self.pvk.vk.gamma_abc_g1.iter().skip(1))
+        {
+            g_ic += base.mul_bigint(input.into_bigint());
+        }
+
+        let qap = P::multi_miller_loop(
+            [
+                <P::G1Affine as Into<P::G1Prepared>>::into(proof.a),
+                g_ic.into_affine().into(),
+                proof.c.into(),
+            ],
+            [
+                proof.b.into(),
+                self.pvk.gamma_g2_neg_pc.clone(),
+                self.pvk.delta_g2_neg_pc.clone(),
+            ],
+        );
+        let test = P::final_exponentiation(qap).ok_or(Groth16Error::InvalidProof)?;
+        if test.0 == self.pvk.alpha_g1_beta_g2 {
+            Ok(())
+        } else {
+            Err(Groth16Error::InvalidProof)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_std::rand::SeedableRng;
+
+    use super::*;
+    use crate::test_utils::TestCircuit;
+
+    #[test]
+    fn verifier_accepts_valid_proof() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let proof = circuit.prove(&mut rng);
+        let public_inputs = circuit.public_inputs().to_vec();
+        let verifier = Groth16Verifier::new(&circuit.pk.vk).unwrap();
+        verifier.verify(&proof, &public_inputs).unwrap();
+        // clones share the same prepared key
+        let cloned = verifier.clone();
+        assert!(Arc::ptr_eq(&verifier.pvk, &cloned.pvk));
+        std::thread::spawn(move || cloned.verify(&proof, &public_inputs).unwrap())
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn verifier_rejects_wrong_public_inputs() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let proof = circuit.prove(&mut rng);
+        let mut public_inputs = circuit.public_inputs().to_vec();
+        let verifier = Groth16Verifier::new(&circuit.pk.vk).unwrap();
+        public_inputs[0] += ark_bn254::Fr::from(1);
+        assert!(matches!(
+            verifier.verify(&proof, &public_inputs),
+            Err(Groth16Error::InvalidProof)
+        ));
+        assert!(matches!(
+            verifier.verify(&proof, &[]),
+            Err(Groth16Error::InvalidPublicInputLength {
+                expected: 1,
+                actual: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn verifier_rejects_malformed_verifying_key() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(0);
+        let circuit = TestCircuit::<Bn254>::setup(&mut rng);
+        let proof = circuit.prove(&mut rng);
+        let mut vk = circuit.pk.vk.clone();
+        vk.gamma_abc_g1.clear();
+        assert!(matches!(
+            Groth16Verifier::new(&vk),
+            Err(Groth16Error::MalformedVerifyingKey)
+        ));
+        assert!(matches!(
+            crate::Groth16::verify(&vk, &proof, &[]),
+            Err(Groth16Error::MalformedVerifyingKey)
+        ));
+    }
+}